@@ -0,0 +1,493 @@
+use first_follow::grammar::Grammar;
+use first_follow::production::Production;
+use first_follow::table::ParseTable;
+use std::time::{Duration, Instant};
+
+// expression-like grammar with one precedence level per operator:
+// level_i -> level_{i+1} tail_i, tail_i -> op_i level_{i+1} tail_i | £,
+// and the last level derives a parenthesised level_0 or one of the literals
+fn generate(levels: usize, literals: usize) -> Grammar {
+    let mut grammar = Grammar {
+        variables: vec![],
+        terminals: vec!["(".to_string(), ")".to_string()],
+        productions: vec![],
+        initial_symbol: "level_0".to_string(),
+    };
+
+    for i in 0..levels {
+        grammar.variables.push(format!("level_{}", i));
+        grammar.variables.push(format!("tail_{}", i));
+        grammar.terminals.push(format!("op_{}", i));
+    }
+    grammar.variables.push(format!("level_{}", levels));
+
+    for i in 0..literals {
+        grammar.terminals.push(format!("literal_{}", i));
+    }
+
+    for i in 0..levels {
+        let level = format!("level_{}", i);
+        let tail = format!("tail_{}", i);
+        let next = format!("level_{}", i + 1);
+
+        grammar.add_production(Production::new(&level, format!("{} {}", next, tail)).unwrap());
+        grammar.add_production(
+            Production::new(&tail, format!("op_{} {} {} | £", i, next, tail)).unwrap(),
+        );
+    }
+
+    let mut last = vec!["( level_0 )".to_string()];
+    last.extend((0..literals).map(|i| format!("literal_{}", i)));
+    grammar
+        .add_production(Production::new(&format!("level_{}", levels), last.join(" | ")).unwrap());
+
+    grammar
+}
+
+fn measure<F: FnMut()>(name: &str, iterations: u32, mut run: F) {
+    let start = Instant::now();
+    for _ in 0..iterations {
+        run();
+    }
+    let elapsed: Duration = start.elapsed() / iterations;
+
+    println!("{:<60} {:>12.3?} per iteration", name, elapsed);
+}
+
+// the baseline gives the same sets and cells as the current implementation
+fn check_baseline(grammar: &Grammar) {
+    let mut analysed = grammar.clone();
+    analysed.compute_follows();
+
+    let sets = baseline::compute_follows(grammar);
+    for p in analysed.productions.iter() {
+        assert_eq!(sets[&p.variable], (p.firsts.clone(), p.follows.clone()));
+    }
+
+    let table = ParseTable::new(&analysed).unwrap();
+    let cells = baseline::parse_table(grammar, &sets);
+    for variable in table.variables.iter() {
+        for lookahead in table.lookaheads.iter() {
+            let cell = cells.get(&(variable.clone(), lookahead.clone()));
+            assert_eq!(table.get(variable, lookahead), cell);
+        }
+    }
+}
+
+fn main() {
+    check_baseline(&generate(5, 10));
+
+    // the baseline table grows with the square of the literal alternatives,
+    // so it only runs on the smaller grammars
+    for (levels, literals, baseline_table) in [(20, 50, true), (60, 200, false), (120, 400, false)]
+    {
+        let grammar = generate(levels, literals);
+        let label = format!(
+            "{} variables, {} terminals",
+            grammar.variables.len(),
+            grammar.terminals.len()
+        );
+
+        measure(&format!("baseline compute_follows ({})", label), 1, || {
+            baseline::compute_follows(&grammar);
+        });
+        measure(&format!("compute_follows ({})", label), 3, || {
+            let mut grammar = grammar.clone();
+            grammar.compute_follows();
+        });
+
+        if baseline_table {
+            let sets = baseline::compute_follows(&grammar);
+            measure(&format!("baseline ParseTable::new ({})", label), 1, || {
+                baseline::parse_table(&grammar, &sets);
+            });
+        }
+
+        let mut analysed = grammar.clone();
+        analysed.compute_follows();
+        measure(&format!("ParseTable::new ({})", label), 3, || {
+            ParseTable::new(&analysed).unwrap();
+        });
+    }
+}
+
+// FIRST/FOLLOW and the LL(1) table as computed before symbols were interned: sets of tokens
+// keyed by variable name, the firsts of a sequence rebuilt from the productions on every call
+// and a table keyed by (variable, lookahead). Kept here so the bench shows both sides.
+mod baseline {
+    use first_follow::grammar::Grammar;
+    use first_follow::table::Alternative;
+    use first_follow::token::{Token, EPSILON};
+    use std::collections::{HashMap, HashSet, VecDeque};
+
+    pub type Sets = HashMap<String, (HashSet<Token>, HashSet<Token>)>;
+    pub type Table = HashMap<(String, Token), Alternative>;
+
+    fn longest_match(grammar: &Grammar, input: &str) -> Option<(Token, usize)> {
+        let mut best: Option<(Token, usize)> = None;
+
+        let candidates = grammar
+            .variables
+            .iter()
+            .map(|v| (Token::Variable(v.clone()), v.as_str()))
+            .chain(
+                grammar
+                    .terminals
+                    .iter()
+                    .map(|t| (Token::Terminal(t.clone()), t.as_str())),
+            )
+            .chain(std::iter::once((Token::Epsilon, EPSILON)));
+
+        for (token, name) in candidates {
+            let longer = best.as_ref().is_none_or(|(_, size)| name.len() > *size);
+
+            if !name.is_empty() && input.starts_with(name) && longer {
+                best = Some((token, name.len()));
+            }
+        }
+
+        best
+    }
+
+    fn tokens(grammar: &Grammar, input: &str) -> Vec<Token> {
+        let mut tokens: Vec<Token> = vec![];
+
+        for word in input.split_whitespace() {
+            let mut rest = word;
+
+            while !rest.is_empty() {
+                match longest_match(grammar, rest) {
+                    Some((token, size)) => {
+                        tokens.push(token);
+                        rest = &rest[size..];
+                    }
+                    None => {
+                        let size = rest.chars().next().map_or(1, |ch| ch.len_utf8());
+                        rest = &rest[size..];
+                    }
+                }
+            }
+        }
+
+        tokens
+    }
+
+    fn derivations(grammar: &Grammar) -> Vec<(String, Vec<Vec<Token>>)> {
+        grammar
+            .productions
+            .iter()
+            .map(|p| {
+                let slices = p.derivation.split('|');
+                (
+                    p.variable.clone(),
+                    slices.map(|s| tokens(grammar, s.trim())).collect(),
+                )
+            })
+            .collect()
+    }
+
+    fn sequence_is_nullable(tokens: &[Token], nullable: &HashSet<String>) -> bool {
+        tokens.iter().all(|t| match t {
+            Token::Epsilon => true,
+            Token::Variable(name) => nullable.contains(name),
+            _ => false,
+        })
+    }
+
+    fn nullable(grammar: &Grammar) -> HashSet<String> {
+        let derivations = derivations(grammar);
+        let mut nullable: HashSet<String> = HashSet::new();
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+
+            for (variable, slices) in derivations.iter() {
+                if !nullable.contains(variable)
+                    && slices.iter().any(|s| sequence_is_nullable(s, &nullable))
+                {
+                    nullable.insert(variable.clone());
+                    changed = true;
+                }
+            }
+        }
+
+        nullable
+    }
+
+    fn sequence_firsts(
+        tokens: &[Token],
+        firsts: &HashMap<String, HashSet<Token>>,
+        nullable: &HashSet<String>,
+    ) -> HashSet<Token> {
+        let mut result: HashSet<Token> = HashSet::new();
+
+        for token in tokens {
+            match token {
+                Token::Epsilon => continue,
+                Token::Variable(name) => {
+                    if let Some(variable_firsts) = firsts.get(name) {
+                        for first in variable_firsts.iter().filter(|t| **t != Token::Epsilon) {
+                            result.insert(first.clone());
+                        }
+                    }
+
+                    if !nullable.contains(name) {
+                        return result;
+                    }
+                }
+                _ => {
+                    result.insert(token.clone());
+                    return result;
+                }
+            }
+        }
+
+        if sequence_is_nullable(tokens, nullable) {
+            result.insert(Token::Epsilon);
+        }
+
+        result
+    }
+
+    // firsts stored per production, as the productions held them
+    fn compute_firsts(grammar: &Grammar) -> Vec<HashSet<Token>> {
+        let nullable = nullable(grammar);
+        let derivations = derivations(grammar);
+
+        let mut dependents: HashMap<String, Vec<usize>> = HashMap::new();
+        for (index, (_, slices)) in derivations.iter().enumerate() {
+            for token in slices.iter().flatten() {
+                if let Token::Variable(name) = token {
+                    let entry = dependents.entry(name.clone()).or_default();
+                    if !entry.contains(&index) {
+                        entry.push(index);
+                    }
+                }
+            }
+        }
+
+        let mut firsts: HashMap<String, HashSet<Token>> = HashMap::new();
+        let mut worklist: VecDeque<usize> = (0..derivations.len()).collect();
+        let mut queued = vec![true; derivations.len()];
+
+        while let Some(index) = worklist.pop_front() {
+            queued[index] = false;
+            let (variable, slices) = &derivations[index];
+
+            let computed: Vec<Token> = slices
+                .iter()
+                .flat_map(|tokens| sequence_firsts(tokens, &firsts, &nullable))
+                .collect();
+
+            let entry = firsts.entry(variable.clone()).or_default();
+            let previous_size = entry.len();
+            entry.extend(computed);
+
+            if entry.len() == previous_size {
+                continue;
+            }
+
+            for &dependent in dependents.get(variable).into_iter().flatten() {
+                if !queued[dependent] {
+                    queued[dependent] = true;
+                    worklist.push_back(dependent);
+                }
+            }
+        }
+
+        grammar
+            .productions
+            .iter()
+            .map(|p| firsts.get(&p.variable).cloned().unwrap_or_default())
+            .collect()
+    }
+
+    // rebuilds the firsts of every variable from the productions on every call
+    fn first_of(grammar: &Grammar, firsts: &[HashSet<Token>], tokens: &[Token]) -> HashSet<Token> {
+        let mut by_variable: HashMap<String, HashSet<Token>> = HashMap::new();
+        for (p, set) in grammar.productions.iter().zip(firsts) {
+            by_variable
+                .entry(p.variable.clone())
+                .or_default()
+                .extend(set.iter().cloned());
+        }
+
+        let nullable: HashSet<String> = by_variable
+            .iter()
+            .filter(|(_, set)| set.contains(&Token::Epsilon))
+            .map(|(variable, _)| variable.clone())
+            .collect();
+
+        sequence_firsts(tokens, &by_variable, &nullable)
+    }
+
+    pub fn compute_follows(grammar: &Grammar) -> Sets {
+        let firsts = compute_firsts(grammar);
+        let nullable = nullable(grammar);
+
+        let mut direct: Vec<(String, Token)> = vec![];
+        let mut placeholders: Vec<(String, String)> = vec![];
+
+        for p in grammar.productions.iter() {
+            if grammar.production_is_initial(p) {
+                direct.push((p.variable.clone(), Token::DollarSign));
+            }
+
+            for slice in p.derivation.split('|') {
+                let slice = tokens(grammar, slice.trim());
+
+                for (index, token) in slice.iter().enumerate() {
+                    if let Token::Variable(name) = token {
+                        let suffix = &slice[index + 1..];
+
+                        for first in first_of(grammar, &firsts, suffix) {
+                            if first != Token::Epsilon {
+                                direct.push((name.clone(), first));
+                            }
+                        }
+
+                        if sequence_is_nullable(suffix, &nullable) && *name != p.variable {
+                            placeholders.push((name.clone(), p.variable.clone()));
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut follows: HashMap<String, HashSet<Token>> = HashMap::new();
+        for (variable, token) in direct {
+            follows.entry(variable).or_default().insert(token);
+        }
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+
+            for (variable, source) in placeholders.iter() {
+                let inherited: Vec<Token> = match follows.get(source) {
+                    Some(set) => set.iter().cloned().collect(),
+                    None => continue,
+                };
+
+                let target = follows.entry(variable.clone()).or_default();
+                let previous_size = target.len();
+                target.extend(inherited);
+                changed |= target.len() != previous_size;
+            }
+        }
+
+        grammar
+            .productions
+            .iter()
+            .zip(firsts)
+            .map(|(p, firsts)| {
+                let follows = follows.get(&p.variable).cloned().unwrap_or_default();
+                (p.variable.clone(), (firsts, follows))
+            })
+            .collect()
+    }
+
+    fn alternatives(grammar: &Grammar) -> Vec<Alternative> {
+        let mut alternatives: Vec<Alternative> = vec![];
+
+        for p in grammar.productions.iter() {
+            let offset = alternatives
+                .iter()
+                .filter(|a| a.variable == p.variable)
+                .count();
+
+            for (index, text) in p.get_derivation_slices().enumerate() {
+                let tokens = tokens(grammar, text.trim());
+                alternatives.push(Alternative::new(&p.variable, offset + index, tokens, text));
+            }
+        }
+
+        alternatives
+    }
+
+    fn follows_of(grammar: &Grammar, sets: &Sets, variable: &str) -> HashSet<Token> {
+        grammar
+            .productions
+            .iter()
+            .filter(|p| p.variable == variable)
+            .flat_map(|p| sets[&p.variable].1.iter().cloned())
+            .collect()
+    }
+
+    fn predict(
+        grammar: &Grammar,
+        sets: &Sets,
+        firsts: &[HashSet<Token>],
+        alternative: &Alternative,
+    ) -> Vec<Token> {
+        let alternative_firsts = first_of(grammar, firsts, &alternative.tokens);
+        let mut lookaheads: Vec<Token> = alternative_firsts
+            .iter()
+            .filter(|t| **t != Token::Epsilon)
+            .cloned()
+            .collect();
+
+        if alternative_firsts.contains(&Token::Epsilon) {
+            for follow in follows_of(grammar, sets, &alternative.variable) {
+                if !lookaheads.contains(&follow) {
+                    lookaheads.push(follow);
+                }
+            }
+        }
+
+        lookaheads
+    }
+
+    // counts the conflicts the way `ll1_conflicts` looked for them, then fills the cells
+    pub fn parse_table(grammar: &Grammar, sets: &Sets) -> Table {
+        let firsts: Vec<HashSet<Token>> = grammar
+            .productions
+            .iter()
+            .map(|p| sets[&p.variable].0.clone())
+            .collect();
+        let alternatives = alternatives(grammar);
+        let mut conflicts = 0;
+
+        for (i, left) in alternatives.iter().enumerate() {
+            for right in alternatives.iter().skip(i + 1) {
+                if left.variable != right.variable {
+                    continue;
+                }
+
+                let left_firsts = first_of(grammar, &firsts, &left.tokens);
+                let right_firsts = first_of(grammar, &firsts, &right.tokens);
+                if left_firsts.intersection(&right_firsts).next().is_some() {
+                    conflicts += 1;
+                }
+
+                let follows = follows_of(grammar, sets, &left.variable);
+                for (nullable, other_firsts) in [(left, &right_firsts), (right, &left_firsts)] {
+                    if !first_of(grammar, &firsts, &nullable.tokens).contains(&Token::Epsilon) {
+                        continue;
+                    }
+
+                    if other_firsts
+                        .intersection(&follows)
+                        .any(|t| *t != Token::Epsilon)
+                    {
+                        conflicts += 1;
+                    }
+                }
+            }
+        }
+        assert_eq!(conflicts, 0, "the generated grammars are LL(1)");
+
+        let mut table: Table = HashMap::new();
+        for alternative in alternatives.iter() {
+            for lookahead in predict(grammar, sets, &firsts, alternative) {
+                table.insert(
+                    (alternative.variable.clone(), lookahead),
+                    alternative.clone(),
+                );
+            }
+        }
+
+        table
+    }
+}
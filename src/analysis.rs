@@ -0,0 +1,168 @@
+use crate::bitset::BitSet;
+use crate::first::{first_sets, sequence_first};
+use crate::follow::follow_sets;
+use crate::grammar::{Grammar, GrammarError};
+use crate::nullable::nullable_variables;
+use crate::symbol::{Symbol, SymbolTable};
+use crate::token::Token;
+use std::collections::HashSet;
+
+/// Nullable variables, firsts and follows of a grammar over interned symbols,
+/// sets are indexed by variable id and hold terminal ids.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Analysis {
+    pub symbols: SymbolTable,
+    pub alternatives: Vec<(usize, Vec<Symbol>)>,
+    pub nullable: BitSet,
+    pub firsts: Vec<BitSet>,
+    pub follows: Vec<BitSet>,
+}
+
+impl Analysis {
+    pub fn new(grammar: &Grammar) -> Analysis {
+        let symbols = SymbolTable::new(grammar);
+        let alternatives = symbols.alternatives(grammar);
+
+        Self::from_alternatives(grammar, symbols, alternatives)
+    }
+
+    /// Fails on the first derivation text that is no declared symbol, which `new` skips.
+    pub fn strict(grammar: &Grammar) -> Result<Analysis, GrammarError> {
+        let symbols = SymbolTable::new(grammar);
+        let alternatives = symbols.strict_alternatives(grammar)?;
+
+        Ok(Self::from_alternatives(grammar, symbols, alternatives))
+    }
+
+    fn from_alternatives(
+        grammar: &Grammar,
+        symbols: SymbolTable,
+        alternatives: Vec<(usize, Vec<Symbol>)>,
+    ) -> Analysis {
+        let nullable = nullable_variables(&symbols, &alternatives);
+        let firsts = first_sets(&symbols, &alternatives, &nullable);
+        let follows = follow_sets(
+            &symbols,
+            &alternatives,
+            &nullable,
+            &firsts,
+            symbols.variable_id(&grammar.initial_symbol),
+        );
+
+        Analysis {
+            symbols,
+            alternatives,
+            nullable,
+            firsts,
+            follows,
+        }
+    }
+
+    pub fn first_of(&self, symbols: &[Symbol]) -> BitSet {
+        sequence_first(symbols, &self.firsts, &self.nullable, &self.symbols)
+    }
+
+    pub fn firsts_of(&self, variable: &str) -> HashSet<Token> {
+        match self.symbols.variable_id(variable) {
+            Some(id) => self.symbols.tokens(&self.firsts[id]),
+            None => HashSet::new(),
+        }
+    }
+
+    pub fn follows_of(&self, variable: &str) -> HashSet<Token> {
+        match self.symbols.variable_id(variable) {
+            Some(id) => self.symbols.tokens(&self.follows[id]),
+            None => HashSet::new(),
+        }
+    }
+}
+
+impl Grammar {
+    pub fn analyze(&self) -> Analysis {
+        Analysis::new(self)
+    }
+
+    pub fn analyze_strict(&self) -> Result<Analysis, GrammarError> {
+        Analysis::strict(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::grammar::{Grammar, GrammarError};
+    use crate::production::Production;
+    use crate::token::Token;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_analysis_matches_productions() {
+        let mut grammar = Grammar {
+            variables: vec![
+                "E".to_string(),
+                "Z".to_string(),
+                "T".to_string(),
+                "Y".to_string(),
+                "F".to_string(),
+            ],
+            terminals: vec![
+                "+".to_string(),
+                "*".to_string(),
+                "(".to_string(),
+                "id".to_string(),
+                ")".to_string(),
+            ],
+            productions: vec![],
+            initial_symbol: "E".to_string(),
+        };
+
+        grammar.add_production(Production::new("E", "TZ".to_string()).unwrap());
+        grammar.add_production(Production::new("Z", "+TZ | £".to_string()).unwrap());
+        grammar.add_production(Production::new("T", "FY".to_string()).unwrap());
+        grammar.add_production(Production::new("Y", "*FY | £".to_string()).unwrap());
+        grammar.add_production(Production::new("F", "(E) | id".to_string()).unwrap());
+
+        let analysis = grammar.analyze();
+        grammar.compute_follows();
+
+        for p in grammar.productions.iter() {
+            assert_eq!(analysis.firsts_of(&p.variable), p.firsts, "{}", p.variable);
+            assert_eq!(
+                analysis.follows_of(&p.variable),
+                p.follows,
+                "{}",
+                p.variable
+            );
+        }
+
+        let expected: HashSet<Token> = vec![Token::Terminal(")".to_string()), Token::DollarSign]
+            .into_iter()
+            .collect();
+        assert_eq!(analysis.follows_of("Z"), expected);
+        assert!(analysis.firsts_of("W").is_empty());
+    }
+
+    #[test]
+    fn test_strict_analysis() {
+        let mut grammar = Grammar {
+            variables: vec!["S".to_string()],
+            terminals: vec!["a".to_string()],
+            productions: vec![],
+            initial_symbol: "S".to_string(),
+        };
+
+        grammar.add_production(Production::new("S", "a S | a ! S".to_string()).unwrap());
+
+        assert_eq!(
+            grammar.analyze_strict(),
+            Err(GrammarError::UnknownSymbol {
+                variable: "S".to_string(),
+                symbol: "!".to_string(),
+                offset: 8,
+            })
+        );
+        assert_eq!(grammar.analyze().firsts_of("S").len(), 1);
+
+        grammar.productions[0].derivation = "a S | £".to_string();
+        assert!(grammar.analyze_strict().is_ok());
+    }
+}
@@ -0,0 +1,435 @@
+use crate::bnf::BnfParser;
+use crate::ebnf::Helper;
+use crate::grammar::Grammar;
+use crate::load::{
+    blank, block_end, find, lex, quoted_end, read_source, source_text, Lexeme, LexemeKind,
+    LoadError, RenamedLiteral,
+};
+use std::fmt;
+use std::path::Path;
+
+const PUNCTUATION: [&str; 11] = [":", "|", ";", "(", ")", "*", "+", "?", "~", ".", ","];
+
+/// Something in the source that has no counterpart in a `Grammar`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Skipped {
+    pub description: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for Skipped {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.description
+        )
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AntlrGrammar {
+    pub grammar: Grammar,
+    pub helpers: Vec<Helper>,
+    pub literals: Vec<RenamedLiteral>,
+    pub skipped: Vec<Skipped>,
+}
+
+impl AntlrGrammar {
+    /// The text a terminal was written as, see `load::RenamedLiteral`.
+    pub fn source_text<'a>(&'a self, terminal: &'a str) -> &'a str {
+        source_text(&self.literals, terminal)
+    }
+
+    /// Lists what the import left out, if anything.
+    pub fn warning(&self) -> Option<String> {
+        if self.skipped.is_empty() {
+            return None;
+        }
+
+        let mut warning = format!("skipped {} item(s):", self.skipped.len());
+        for skipped in self.skipped.iter() {
+            warning += &format!("\n  {}", skipped);
+        }
+
+        Some(warning)
+    }
+}
+
+/// Imports the parser rules of an ANTLR4 grammar. Alternatives, `( )` groups and the `?`, `*`
+/// and `+` operators are desugared like in `ebnf::parse`, string literals and token references
+/// become terminals and `EOF` is left to the implicit `$`. Literals such as `'||'` are renamed
+/// as in `yacc::parse`.
+///
+/// Lexer rules, actions, predicates, `options`, `tokens` and `@` blocks are skipped and listed
+/// in `skipped`. Element and alternative labels, rule arguments, element options such as
+/// `<assoc=right>` and non-greedy markers are dropped silently since they do not change the
+/// language. `~` sets and the `.` wildcard cannot be expressed and are reported as errors.
+pub fn parse(input: &str) -> Result<AntlrGrammar, LoadError> {
+    let mut skipped: Vec<Skipped> = vec![];
+    let stripped = strip_code(input, &mut skipped);
+    let lexemes = parser_rules(lex(&stripped, &PUNCTUATION)?, &mut skipped)?;
+
+    let ebnf = BnfParser::new(lexemes).parse_with_helpers()?;
+    skipped.sort_by_key(|s| (s.line, s.column));
+
+    Ok(AntlrGrammar {
+        grammar: ebnf.grammar,
+        helpers: ebnf.helpers,
+        literals: ebnf.literals,
+        skipped,
+    })
+}
+
+pub fn read(path: impl AsRef<Path>) -> Result<AntlrGrammar, LoadError> {
+    parse(&read_source(path)?)
+}
+
+fn is_identifier(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_'
+}
+
+// blanks out code, labels and options, and records what was skipped
+fn strip_code(input: &str, skipped: &mut Vec<Skipped>) -> String {
+    let chars: Vec<char> = input.chars().collect();
+    let mut output = String::with_capacity(input.len());
+    let (mut line, mut column) = (1, 1);
+    let mut last_word = String::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let start = i;
+        let next = |from: usize| (from..chars.len()).find(|k| !chars[*k].is_whitespace());
+
+        match chars[i] {
+            '\'' | '"' => {
+                i = quoted_end(&chars, i);
+                output.extend(&chars[start..i]);
+            }
+            '/' if chars.get(i + 1) == Some(&'/') => {
+                i = (i..chars.len())
+                    .find(|k| chars[*k] == '\n')
+                    .unwrap_or(chars.len());
+                blank(&mut output, &chars[start..i]);
+            }
+            '/' if chars.get(i + 1) == Some(&'*') => {
+                i = find(&chars, i + 2, "*/").map_or(chars.len(), |end| end + 2);
+                blank(&mut output, &chars[start..i]);
+            }
+            '{' => {
+                i = block_end(&chars, i).unwrap_or(chars.len());
+
+                let description = if ["options", "tokens", "channels"].contains(&last_word.as_str())
+                    || last_word.starts_with('@')
+                {
+                    format!("'{}' block", last_word)
+                } else if next(i).map(|k| chars[k]) == Some('?') {
+                    i = next(i).unwrap() + 1;
+                    "predicate".to_string()
+                } else {
+                    "action".to_string()
+                };
+
+                skipped.push(Skipped {
+                    description,
+                    line,
+                    column,
+                });
+                blank(&mut output, &chars[start..i]);
+            }
+            // rule arguments, return values and locals
+            '[' => {
+                i += 1;
+                while i < chars.len() && chars[i] != ']' {
+                    i += if chars[i] == '\\' { 2 } else { 1 };
+                }
+                i = (i + 1).min(chars.len());
+                blank(&mut output, &chars[start..i]);
+            }
+            // element options such as <assoc=right>
+            '<' => {
+                i = find(&chars, i, ">").map_or(chars.len(), |end| end + 1);
+                blank(&mut output, &chars[start..i]);
+            }
+            // alternative labels
+            '#' => {
+                i += 1;
+                while i < chars.len() && chars[i].is_whitespace() && chars[i] != '\n' {
+                    i += 1;
+                }
+                while i < chars.len() && is_identifier(chars[i]) {
+                    i += 1;
+                }
+                blank(&mut output, &chars[start..i]);
+            }
+            // non-greedy operators
+            '?' if matches!(output.trim_end().chars().last(), Some('*' | '+' | '?')) => {
+                i += 1;
+                output.push(' ');
+            }
+            ch if is_identifier(ch) || ch == '@' => {
+                i += 1;
+                while i < chars.len()
+                    && (is_identifier(chars[i])
+                        || chars[i] == ':' && chars.get(i + 1) == Some(&':'))
+                {
+                    i += if chars[i] == ':' { 2 } else { 1 };
+                }
+                last_word = chars[start..i].iter().collect();
+
+                // element labels, `x=` and `x+=`
+                let label = match next(i) {
+                    Some(k) if chars[k] == '=' => Some(k + 1),
+                    Some(k) if chars[k] == '+' && chars.get(k + 1) == Some(&'=') => Some(k + 2),
+                    _ => None,
+                };
+
+                match label {
+                    Some(end) => {
+                        i = end;
+                        blank(&mut output, &chars[start..i]);
+                    }
+                    None => output.extend(&chars[start..i]),
+                }
+            }
+            ch => {
+                i += 1;
+                output.push(ch);
+            }
+        }
+
+        for ch in chars[start..i].iter() {
+            if *ch == '\n' {
+                line += 1;
+                column = 1;
+            } else {
+                column += 1;
+            }
+        }
+    }
+
+    output
+}
+
+fn is_punct(lexeme: Option<&Lexeme>, punct: &str) -> bool {
+    matches!(lexeme, Some(Lexeme { kind: LexemeKind::Punct(p), .. }) if *p == punct)
+}
+
+fn word(lexeme: &Lexeme) -> Option<&str> {
+    match &lexeme.kind {
+        LexemeKind::Word(word) => Some(word),
+        _ => None,
+    }
+}
+
+// keeps the parser rules as `name : body ;` and drops everything else
+fn parser_rules(
+    lexemes: Vec<Lexeme>,
+    skipped: &mut Vec<Skipped>,
+) -> Result<Vec<Lexeme>, LoadError> {
+    let lexemes: Vec<Lexeme> = lexemes
+        .into_iter()
+        .filter(|l| l.kind != LexemeKind::Newline)
+        .collect();
+    let mut rules: Vec<Lexeme> = vec![];
+    let mut i = 0;
+
+    let statement_end = |from: usize| {
+        (from..lexemes.len())
+            .find(|k| is_punct(lexemes.get(*k), ";"))
+            .map_or(lexemes.len(), |k| k + 1)
+    };
+
+    while i < lexemes.len() {
+        let lexeme = &lexemes[i];
+        let name = match word(lexeme) {
+            Some(name) => name,
+            None => {
+                return Err(lexeme.error(format!("expected a rule, found {}", lexeme.describe())))
+            }
+        };
+
+        // the colon comes after the name and whatever arguments, returns or locals declare
+        let colon = (i..lexemes.len()).find(|k| !matches!(lexemes[*k].kind, LexemeKind::Word(_)));
+        let is_rule = colon.is_some_and(|k| is_punct(lexemes.get(k), ":"));
+
+        match name {
+            "options" | "tokens" | "channels" | "catch" | "finally" => i += 1,
+            _ if name.starts_with('@') => i += 1,
+            "grammar" | "parser" | "lexer" | "import" | "mode" if !is_rule => i = statement_end(i),
+            _ if !is_rule => {
+                return Err(lexeme.error(format!("expected ':' after '{}'", name)));
+            }
+            _ => {
+                let modifiers = ["fragment", "public", "private", "protected"];
+                let (rule, fragment) = match modifiers.contains(&name) && colon != Some(i + 1) {
+                    true => (&lexemes[i + 1], name == "fragment"),
+                    false => (lexeme, false),
+                };
+                let rule_name = word(rule).unwrap();
+                let end = statement_end(i);
+
+                if fragment || rule_name.starts_with(char::is_uppercase) {
+                    skipped.push(Skipped {
+                        description: format!("lexer rule '{}'", rule_name),
+                        line: rule.line,
+                        column: rule.column,
+                    });
+                    i = end;
+                    continue;
+                }
+
+                rules.push(rule.clone());
+                for lexeme in lexemes[colon.unwrap()..end].iter() {
+                    match &lexeme.kind {
+                        LexemeKind::Word(word) if word == "EOF" => {}
+                        LexemeKind::Punct(punct @ ("~" | "." | ",")) => {
+                            return Err(lexeme
+                                .error(format!("'{}' is not supported in parser rules", punct)))
+                        }
+                        _ => rules.push(lexeme.clone()),
+                    }
+                }
+                i = end;
+            }
+        }
+    }
+
+    Ok(rules)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::antlr::parse;
+    use crate::load::LoadError;
+
+    const EXPR: &str = r#"
+grammar Expr;
+
+options { language = Java; }
+@header { package expr; }
+
+prog : stat+ EOF ;
+
+stat : e=expr NEWLINE            # printExpr
+     | ID '=' expr NEWLINE      # assign
+     | NEWLINE                  # blank
+     ;
+
+expr returns [int value]
+     : <assoc=right> expr '^' expr
+     | expr op=('*'|'/') expr   { $value = 0; }
+     | INT
+     | ID ( '(' args+=expr? ')' )*?
+     | '(' expr ')'
+     ;
+
+ID  : [a-zA-Z]+ ;
+fragment DIGIT : [0-9] ;
+INT : DIGIT+ ;
+NEWLINE : '\r'? '\n' ;
+WS  : [ \t]+ -> skip ;
+"#;
+
+    #[test]
+    fn test_literals_with_bars() {
+        let antlr = parse("grammar G;\ne : e '||' e | e '|=' e | '£' | ID ;").unwrap();
+
+        assert_eq!(
+            antlr.grammar.productions[0].derivation,
+            "e ¦¦ e | e ¦= e | ₤ | ID"
+        );
+        assert_eq!(antlr.source_text("¦="), "|=");
+        assert_eq!(antlr.source_text("₤"), "£");
+
+        let mut grammar = antlr.grammar;
+        grammar.compute_follows();
+        assert!(!grammar.ll1_conflicts().is_ll1());
+    }
+
+    #[test]
+    fn test_import_parser_rules() {
+        let antlr = parse(EXPR).unwrap();
+        let grammar = &antlr.grammar;
+
+        assert_eq!(grammar.initial_symbol, "prog");
+        assert_eq!(
+            grammar.variables,
+            vec!["prog", "prog_1", "stat", "expr", "expr_1", "expr_2", "expr_3"]
+        );
+        assert_eq!(
+            grammar.terminals,
+            vec!["NEWLINE", "ID", "=", "^", "INT", "(", ")", "*", "/"]
+        );
+
+        let derivations: Vec<(&str, &str)> = grammar
+            .productions
+            .iter()
+            .map(|p| (p.variable.as_str(), p.derivation.as_str()))
+            .collect();
+        assert_eq!(
+            derivations,
+            vec![
+                ("prog", "stat prog_1"),
+                ("prog_1", "stat prog_1 | £"),
+                ("stat", "expr NEWLINE | ID = expr NEWLINE | NEWLINE"),
+                (
+                    "expr",
+                    "expr ^ expr | expr expr_1 expr | INT | ID expr_3 | ( expr )"
+                ),
+                ("expr_1", "* | /"),
+                ("expr_2", "expr | £"),
+                ("expr_3", "( expr_2 ) expr_3 | £"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_skipped_items_are_listed() {
+        let antlr = parse(EXPR).unwrap();
+        let descriptions: Vec<&str> = antlr
+            .skipped
+            .iter()
+            .map(|s| s.description.as_str())
+            .collect();
+
+        assert_eq!(
+            descriptions,
+            vec![
+                "'options' block",
+                "'@header' block",
+                "action",
+                "lexer rule 'ID'",
+                "lexer rule 'DIGIT'",
+                "lexer rule 'INT'",
+                "lexer rule 'NEWLINE'",
+                "lexer rule 'WS'",
+            ]
+        );
+        assert!(antlr
+            .warning()
+            .unwrap()
+            .contains("line 16, column 33: action"));
+    }
+
+    #[test]
+    fn test_unsupported_elements() {
+        assert_eq!(
+            parse("grammar A;\ns : ~';' ;"),
+            Err(LoadError::Syntax {
+                line: 2,
+                column: 5,
+                message: "'~' is not supported in parser rules".to_string()
+            })
+        );
+        assert_eq!(
+            parse("s a ;"),
+            Err(LoadError::Syntax {
+                line: 1,
+                column: 1,
+                message: "expected ':' after 's'".to_string()
+            })
+        );
+    }
+}
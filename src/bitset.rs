@@ -0,0 +1,158 @@
+const WORD_BITS: usize = 64;
+
+/// Fixed capacity set of dense ids, one bit per id.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct BitSet {
+    words: Vec<u64>,
+    capacity: usize,
+}
+
+impl BitSet {
+    pub fn new(capacity: usize) -> BitSet {
+        BitSet {
+            words: vec![0; capacity.div_ceil(WORD_BITS)],
+            capacity,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn insert(&mut self, id: usize) -> bool {
+        assert!(id < self.capacity, "id {} out of bounds", id);
+
+        let (word, mask) = (id / WORD_BITS, 1 << (id % WORD_BITS));
+        let absent = self.words[word] & mask == 0;
+        self.words[word] |= mask;
+
+        absent
+    }
+
+    pub fn remove(&mut self, id: usize) -> bool {
+        if id >= self.capacity {
+            return false;
+        }
+
+        let (word, mask) = (id / WORD_BITS, 1 << (id % WORD_BITS));
+        let present = self.words[word] & mask != 0;
+        self.words[word] &= !mask;
+
+        present
+    }
+
+    pub fn contains(&self, id: usize) -> bool {
+        id < self.capacity && self.words[id / WORD_BITS] & (1 << (id % WORD_BITS)) != 0
+    }
+
+    /// Adds every id of the other set, telling whether this set grew.
+    pub fn union_with(&mut self, other: &BitSet) -> bool {
+        let mut changed = false;
+
+        for (word, other_word) in self.words.iter_mut().zip(other.words.iter()) {
+            let merged = *word | other_word;
+            changed |= merged != *word;
+            *word = merged;
+        }
+
+        changed
+    }
+
+    /// Like `union_with`, leaving the given id out.
+    pub fn union_without(&mut self, other: &BitSet, excluded: usize) -> bool {
+        let mut changed = false;
+
+        for (index, (word, other_word)) in self.words.iter_mut().zip(other.words.iter()).enumerate()
+        {
+            let mut incoming = *other_word;
+            if index == excluded / WORD_BITS {
+                incoming &= !(1 << (excluded % WORD_BITS));
+            }
+
+            let merged = *word | incoming;
+            changed |= merged != *word;
+            *word = merged;
+        }
+
+        changed
+    }
+
+    pub fn intersection(&self, other: &BitSet) -> BitSet {
+        BitSet {
+            words: self
+                .words
+                .iter()
+                .zip(other.words.iter())
+                .map(|(a, b)| a & b)
+                .collect(),
+            capacity: self.capacity.min(other.capacity),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|w| *w == 0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(index, word)| {
+            let mut bits = *word;
+
+            std::iter::from_fn(move || {
+                if bits == 0 {
+                    return None;
+                }
+
+                let offset = bits.trailing_zeros() as usize;
+                bits &= bits - 1;
+
+                Some(index * WORD_BITS + offset)
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::bitset::BitSet;
+
+    #[test]
+    fn test_insert_and_remove() {
+        let mut set = BitSet::new(130);
+
+        assert!(set.insert(0));
+        assert!(set.insert(64));
+        assert!(set.insert(129));
+        assert!(!set.insert(64));
+
+        assert_eq!(set.len(), 3);
+        assert!(set.contains(129));
+        assert!(!set.contains(1));
+        assert!(!set.contains(500));
+
+        assert!(set.remove(64));
+        assert!(!set.remove(64));
+        assert_eq!(set.iter().collect::<Vec<usize>>(), vec![0, 129]);
+    }
+
+    #[test]
+    fn test_union() {
+        let mut left = BitSet::new(70);
+        let mut right = BitSet::new(70);
+        left.insert(1);
+        right.insert(1);
+        right.insert(65);
+        right.insert(69);
+
+        assert!(left.union_without(&right, 69));
+        assert_eq!(left.iter().collect::<Vec<usize>>(), vec![1, 65]);
+        assert!(!left.union_without(&right, 69));
+
+        assert!(left.union_with(&right));
+        assert!(!left.union_with(&right));
+        assert_eq!(left.intersection(&right), right);
+    }
+}
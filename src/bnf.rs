@@ -0,0 +1,517 @@
+use crate::ebnf::{Construct, EbnfGrammar, Helper};
+use crate::grammar::Grammar;
+use crate::load::{
+    lex, read_source, Item, Lexeme, LexemeKind, LoadError, RuleAlternative, RuleSet,
+};
+use crate::token::EPSILON;
+use std::path::Path;
+
+const PUNCTUATION: [&str; 4] = ["->", "::=", "|", ";"];
+
+/// Reads a grammar written as `A -> α | β` rules:
+///
+/// ```text
+/// # comments start with '#'
+/// %token id + *
+/// %start E
+/// E -> T E'
+/// E' -> + T E' | £ ; T -> F T'
+/// ```
+///
+/// A rule continues on the next line when one of them has a `|` at the line break, and several
+/// rules may share a line when separated by `;`.
+/// Symbols are separated by whitespace, quoted symbols are always terminals and `£`, `ε` or
+/// an empty alternative derive epsilon. `%token` and `%variable` declare symbols and `%start`
+/// picks the start symbol, which otherwise is the variable of the first rule.
+pub fn parse(input: &str) -> Result<Grammar, LoadError> {
+    let lexemes = lex(input, &PUNCTUATION)?;
+    BnfParser::new(lexemes).parse()
+}
+
+pub fn read(path: impl AsRef<Path>) -> Result<Grammar, LoadError> {
+    parse(&read_source(path)?)
+}
+
+impl Grammar {
+    pub fn from_bnf(input: &str) -> Result<Grammar, LoadError> {
+        parse(input)
+    }
+}
+
+pub(crate) fn is_epsilon(word: &str) -> bool {
+    word == EPSILON || word == "ε"
+}
+
+fn unclosed(opening: &Lexeme) -> LoadError {
+    opening.error(format!("{} is never closed", opening.describe()))
+}
+
+pub(crate) struct BnfParser {
+    lexemes: Vec<Lexeme>,
+    position: usize,
+    rules: RuleSet,
+    // variables generated for EBNF constructs, which only show up with the EBNF punctuation
+    helpers: Vec<Helper>,
+    pending: Vec<(Lexeme, String, Vec<RuleAlternative>)>,
+}
+
+impl BnfParser {
+    pub(crate) fn new(lexemes: Vec<Lexeme>) -> BnfParser {
+        BnfParser {
+            lexemes,
+            position: 0,
+            rules: RuleSet::default(),
+            helpers: vec![],
+            pending: vec![],
+        }
+    }
+
+    fn peek(&self, offset: usize) -> Option<&Lexeme> {
+        self.lexemes.get(self.position + offset)
+    }
+
+    fn end(&self) -> Lexeme {
+        let last = self.lexemes.last();
+
+        Lexeme {
+            kind: LexemeKind::Newline,
+            line: last.map_or(1, |l| l.line),
+            column: last.map_or(1, |l| l.column),
+        }
+    }
+
+    pub(crate) fn parse(self) -> Result<Grammar, LoadError> {
+        self.parse_with_helpers().map(|ebnf| ebnf.grammar)
+    }
+
+    pub(crate) fn parse_with_helpers(mut self) -> Result<EbnfGrammar, LoadError> {
+        while let Some(lexeme) = self.peek(0).cloned() {
+            match &lexeme.kind {
+                LexemeKind::Newline | LexemeKind::Punct(";") => self.position += 1,
+                LexemeKind::Directive(name) => {
+                    self.position += 1;
+                    self.directive(&lexeme, name)?;
+                }
+                LexemeKind::Word(variable) => {
+                    self.position += 1;
+                    self.arrow(&lexeme, variable)?;
+                    let alternatives = self.alternatives(variable, None)?;
+                    self.rules.add_rule(&lexeme, variable, alternatives)?;
+
+                    // helpers come right after the rule that introduced them
+                    for (lexeme, helper, alternatives) in std::mem::take(&mut self.pending) {
+                        self.rules.add_rule(&lexeme, &helper, alternatives)?;
+                    }
+                }
+                _ => {
+                    return Err(lexeme.error(format!(
+                        "expected a rule or a declaration, found {}",
+                        lexeme.describe()
+                    )))
+                }
+            }
+        }
+
+        let end = self.end();
+        let (grammar, literals) = self.rules.into_grammar(&end)?;
+
+        Ok(EbnfGrammar {
+            grammar,
+            helpers: self.helpers,
+            literals,
+        })
+    }
+
+    fn arrow(&mut self, variable: &Lexeme, name: &str) -> Result<(), LoadError> {
+        match self.peek(0) {
+            Some(Lexeme {
+                kind: LexemeKind::Punct("->" | "::=" | ":"),
+                ..
+            }) => {
+                self.position += 1;
+                Ok(())
+            }
+            Some(lexeme) => Err(lexeme.error(format!(
+                "expected '->' after '{}', found {}",
+                name,
+                lexeme.describe()
+            ))),
+            None => Err(variable.error(format!("expected '->' after '{}'", name))),
+        }
+    }
+
+    // a rule ends at ';', at a declaration, at the start of the next rule or at the end of input,
+    // a group only ends at its closing bracket
+    fn alternatives(
+        &mut self,
+        variable: &str,
+        group: Option<(&Lexeme, &'static str)>,
+    ) -> Result<Vec<RuleAlternative>, LoadError> {
+        let mut alternatives: Vec<RuleAlternative> = vec![vec![]];
+
+        loop {
+            let lexeme = match (self.peek(0).cloned(), group) {
+                (Some(lexeme), _) => lexeme,
+                (None, None) => break,
+                (None, Some((opening, _))) => return Err(unclosed(opening)),
+            };
+
+            match (&lexeme.kind, group) {
+                (LexemeKind::Punct(punct), Some((_, closing))) if *punct == closing => {
+                    self.position += 1;
+                    break;
+                }
+                (LexemeKind::Punct(";") | LexemeKind::Directive(_), Some((opening, _))) => {
+                    return Err(unclosed(opening))
+                }
+                (LexemeKind::Newline, Some(_)) => {}
+                (LexemeKind::Punct(";") | LexemeKind::Directive(_), None) => break,
+                (LexemeKind::Word(_), None) if self.starts_rule() => break,
+                (LexemeKind::Newline, None) if !self.continues_rule() => break,
+                (LexemeKind::Newline, None) => {}
+                (LexemeKind::Punct("|"), _) => alternatives.push(vec![]),
+                (LexemeKind::Word(word), _) if is_epsilon(word) => alternatives
+                    .last_mut()
+                    .unwrap()
+                    .push((Item::Epsilon, lexeme.clone())),
+                (LexemeKind::Word(_) | LexemeKind::Literal(_), _)
+                | (LexemeKind::Punct("(" | "[" | "{"), _) => {
+                    let items = self.operand(variable)?;
+                    alternatives.last_mut().unwrap().extend(items);
+                    continue;
+                }
+                (LexemeKind::Punct(punct @ ("*" | "+" | "?")), _) => {
+                    return Err(lexeme.error(format!("'{}' must follow a symbol or a group", punct)))
+                }
+                (LexemeKind::Punct(punct), _) => {
+                    return Err(lexeme.error(format!("unexpected '{}' inside a rule", punct)))
+                }
+            }
+
+            self.position += 1;
+        }
+
+        Ok(alternatives)
+    }
+
+    // a symbol or a group followed by any number of postfix operators, the constructs that need
+    // more than a sequence of symbols are replaced by helper variables
+    fn operand(&mut self, variable: &str) -> Result<RuleAlternative, LoadError> {
+        let start = self.position;
+        let lexeme = self.lexemes[start].clone();
+        self.position += 1;
+
+        let mut operand: Vec<RuleAlternative> = match &lexeme.kind {
+            LexemeKind::Word(word) => vec![vec![(Item::Symbol(word.clone()), lexeme.clone())]],
+            LexemeKind::Literal(text) => {
+                vec![vec![(
+                    Item::Literal(self.rules.literal(text)),
+                    lexeme.clone(),
+                )]]
+            }
+            LexemeKind::Punct(opening) => {
+                let (closing, construct) = match *opening {
+                    "(" => (")", Construct::Group),
+                    "[" => ("]", Construct::Optional),
+                    _ => ("}", Construct::Repetition),
+                };
+                let inner = self.alternatives(variable, Some((&lexeme, closing)))?;
+
+                match construct {
+                    Construct::Group => inner,
+                    _ => vec![self.helper(variable, construct, start, inner)],
+                }
+            }
+            _ => unreachable!("operands start with a symbol or an opening bracket"),
+        };
+
+        while let Some(LexemeKind::Punct(punct @ ("*" | "+" | "?"))) =
+            self.peek(0).map(|l| l.kind.clone())
+        {
+            self.position += 1;
+
+            let single = self.single(variable, start, operand);
+            operand = match punct {
+                "?" => vec![self.helper(variable, Construct::Optional, start, vec![single])],
+                "*" => vec![self.helper(variable, Construct::Repetition, start, vec![single])],
+                _ => {
+                    let mut items = single.clone();
+                    let tail = self.helper(variable, Construct::OneOrMore, start, vec![single]);
+                    items.extend(tail);
+                    vec![items]
+                }
+            };
+        }
+
+        Ok(self.single(variable, start, operand))
+    }
+
+    // a sequence standing for all the alternatives, a group helper when there are several
+    fn single(
+        &mut self,
+        variable: &str,
+        start: usize,
+        mut alternatives: Vec<RuleAlternative>,
+    ) -> RuleAlternative {
+        if alternatives.len() == 1 {
+            return alternatives.remove(0);
+        }
+
+        self.helper(variable, Construct::Group, start, alternatives)
+    }
+
+    // `{ x }`, `x*` and the tail of `x+` repeat through right recursion, `[ x ]` and `x?` get
+    // an epsilon alternative
+    fn helper(
+        &mut self,
+        variable: &str,
+        construct: Construct,
+        start: usize,
+        mut alternatives: Vec<RuleAlternative>,
+    ) -> RuleAlternative {
+        let lexeme = self.lexemes[start].clone();
+        let name = self.fresh_name(variable);
+        let reference = (Item::Symbol(name.clone()), lexeme.clone());
+
+        if let Construct::Repetition | Construct::OneOrMore = construct {
+            for alternative in alternatives.iter_mut() {
+                alternative.push(reference.clone());
+            }
+        }
+        if construct != Construct::Group {
+            alternatives.push(vec![(Item::Epsilon, lexeme.clone())]);
+        }
+
+        let text: Vec<String> = self.lexemes[start..self.position]
+            .iter()
+            .filter(|l| l.kind != LexemeKind::Newline)
+            .map(|l| l.text())
+            .collect();
+
+        self.helpers.push(Helper {
+            variable: name.clone(),
+            rule: variable.to_string(),
+            construct,
+            text: text.join(" "),
+            line: lexeme.line,
+            column: lexeme.column,
+        });
+        self.pending.push((lexeme.clone(), name, alternatives));
+
+        vec![reference]
+    }
+
+    fn fresh_name(&self, variable: &str) -> String {
+        (1..)
+            .map(|n| format!("{}_{}", variable, n))
+            .find(|name| {
+                !self.helpers.iter().any(|h| h.variable == *name)
+                    && !self.lexemes.iter().any(|l| match &l.kind {
+                        LexemeKind::Word(word) | LexemeKind::Literal(word) => word == name,
+                        _ => false,
+                    })
+            })
+            .unwrap()
+    }
+
+    // a rule goes on past the end of its line when the line ends with '->' or '|', or the
+    // next line starts with '|'
+    fn continues_rule(&self) -> bool {
+        let previous = self.lexemes[..self.position].last().map(|l| l.kind.clone());
+        if let Some(LexemeKind::Punct("|" | "->" | "::=")) = previous {
+            return true;
+        }
+
+        let next = self.lexemes[self.position..]
+            .iter()
+            .find(|l| l.kind != LexemeKind::Newline);
+        matches!(
+            next,
+            Some(Lexeme {
+                kind: LexemeKind::Punct("|"),
+                ..
+            })
+        )
+    }
+
+    fn starts_rule(&self) -> bool {
+        let mut offset = 1;
+        while let Some(Lexeme {
+            kind: LexemeKind::Newline,
+            ..
+        }) = self.peek(offset)
+        {
+            offset += 1;
+        }
+
+        matches!(
+            self.peek(offset),
+            Some(Lexeme {
+                kind: LexemeKind::Punct("->" | "::=" | ":"),
+                ..
+            })
+        )
+    }
+
+    // declarations take the rest of their line
+    fn directive(&mut self, directive: &Lexeme, name: &str) -> Result<(), LoadError> {
+        let mut arguments: Vec<Lexeme> = vec![];
+        while let Some(lexeme) = self.peek(0) {
+            if lexeme.kind == LexemeKind::Newline {
+                break;
+            }
+            arguments.push(lexeme.clone());
+            self.position += 1;
+        }
+
+        let mut names: Vec<(&Lexeme, String)> = vec![];
+        for lexeme in arguments.iter() {
+            match &lexeme.kind {
+                LexemeKind::Word(word) | LexemeKind::Literal(word) => {
+                    names.push((lexeme, word.clone()))
+                }
+                _ => {
+                    return Err(lexeme.error(format!(
+                        "expected a symbol name, found {}",
+                        lexeme.describe()
+                    )))
+                }
+            }
+        }
+
+        match name {
+            "token" | "terminal" => {
+                for (lexeme, name) in names {
+                    self.rules.declare_terminal(lexeme, &name)?;
+                }
+            }
+            "variable" | "nonterminal" => {
+                for (lexeme, name) in names {
+                    self.rules.declare_variable(lexeme, &name)?;
+                }
+            }
+            "start" => {
+                if names.len() != 1 {
+                    return Err(directive.error("'%start' takes exactly one variable".to_string()));
+                }
+                if self.rules.start.is_some() {
+                    return Err(directive.error("the start symbol is already set".to_string()));
+                }
+
+                let (lexeme, name) = &names[0];
+                self.rules.declare_variable(lexeme, name)?;
+                self.rules.start = Some(name.clone());
+            }
+            _ => return Err(directive.error(format!("unknown declaration '%{}'", name))),
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::bnf::parse;
+    use crate::grammar::Grammar;
+    use crate::load::LoadError;
+    use crate::production::Production;
+
+    #[test]
+    fn test_parse_grammar() {
+        let grammar = parse(
+            "# expressions
+            E -> T E'
+            E' -> + T E' | £ ; T -> F T'
+            T' -> * F T'
+                | ε
+            F -> ( E ) | id",
+        )
+        .unwrap();
+
+        let mut expected = Grammar {
+            variables: vec![
+                "E".to_string(),
+                "E'".to_string(),
+                "T".to_string(),
+                "T'".to_string(),
+                "F".to_string(),
+            ],
+            terminals: vec![
+                "+".to_string(),
+                "*".to_string(),
+                "(".to_string(),
+                ")".to_string(),
+                "id".to_string(),
+            ],
+            productions: vec![],
+            initial_symbol: "E".to_string(),
+        };
+
+        expected.add_production(Production::new("E", "T E'".to_string()).unwrap());
+        expected.add_production(Production::new("E'", "+ T E' | £".to_string()).unwrap());
+        expected.add_production(Production::new("T", "F T'".to_string()).unwrap());
+        expected.add_production(Production::new("T'", "* F T' | £".to_string()).unwrap());
+        expected.add_production(Production::new("F", "( E ) | id".to_string()).unwrap());
+
+        assert_eq!(grammar, expected);
+    }
+
+    #[test]
+    fn test_declarations() {
+        let grammar = parse(
+            "%token b a
+            %start S
+            S -> A | b ';' |
+            A -> a A ; A -> a",
+        )
+        .unwrap();
+
+        assert_eq!(grammar.initial_symbol, "S");
+        assert_eq!(grammar.variables, vec!["S", "A"]);
+        assert_eq!(grammar.terminals, vec!["b", "a", ";"]);
+        assert_eq!(
+            grammar.get_production_by_var("S").unwrap().derivation,
+            "A | b ; | £"
+        );
+        assert_eq!(
+            grammar.get_production_by_var("A").unwrap().derivation,
+            "a A | a"
+        );
+    }
+
+    #[test]
+    fn test_syntax_errors() {
+        let cases = vec![
+            ("E -> a ; T a", 1, 12, "expected '->' after 'T', found 'a'"),
+            (
+                "E -> T Z\nZ + T",
+                2,
+                3,
+                "expected '->' after 'Z', found '+'",
+            ),
+            ("E -> a\n  | -> c", 2, 5, "unexpected '->' inside a rule"),
+            ("E -> 'a", 1, 6, "unterminated literal"),
+            (
+                "%token E\nE -> a",
+                2,
+                1,
+                "'E' is already declared as a terminal",
+            ),
+            ("%precedence a", 1, 1, "unknown declaration '%precedence'"),
+            ("# nothing\n", 1, 10, "the grammar has no rules"),
+        ];
+
+        for (input, line, column, message) in cases {
+            assert_eq!(
+                parse(input),
+                Err(LoadError::Syntax {
+                    line,
+                    column,
+                    message: message.to_string()
+                }),
+                "Testing {:?}",
+                input
+            );
+        }
+    }
+}
@@ -0,0 +1,293 @@
+use crate::analysis::Analysis;
+use crate::bitset::BitSet;
+use crate::grammar::Grammar;
+use crate::table::Alternative;
+use crate::token::Token;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ConflictKind {
+    FirstFirst,
+    FirstFollow,
+}
+
+impl fmt::Display for ConflictKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConflictKind::FirstFirst => f.write_str("FIRST/FIRST"),
+            ConflictKind::FirstFollow => f.write_str("FIRST/FOLLOW"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Conflict {
+    pub kind: ConflictKind,
+    pub variable: String,
+    pub alternatives: (Alternative, Alternative),
+    pub lookaheads: Vec<Token>,
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lookaheads: Vec<String> = self.lookaheads.iter().map(|t| t.to_string()).collect();
+
+        write!(
+            f,
+            "{} conflict on {} between '{}' and '{}' for {}",
+            self.kind,
+            self.variable,
+            self.alternatives.0,
+            self.alternatives.1,
+            lookaheads.join(",")
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConflictReport {
+    pub conflicts: Vec<Conflict>,
+}
+
+impl ConflictReport {
+    pub fn is_ll1(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+
+    pub fn for_variable(&self, variable: &str) -> Vec<&Conflict> {
+        self.conflicts
+            .iter()
+            .filter(|c| c.variable == variable)
+            .collect()
+    }
+}
+
+impl fmt::Display for ConflictReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for conflict in self.conflicts.iter() {
+            writeln!(f, "{}", conflict)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConflictReport {}
+
+impl Grammar {
+    /// Lists every pair of alternatives of the same variable that an LL(1) table could not tell
+    /// apart.
+    pub fn ll1_conflicts(&self) -> ConflictReport {
+        self.analyze().conflicts(&self.alternatives())
+    }
+}
+
+impl Analysis {
+    // the alternatives are those the analysis was built from, in the same order
+    pub(crate) fn conflicts(&self, alternatives: &[Alternative]) -> ConflictReport {
+        let epsilon = self.symbols.epsilon();
+        let firsts: Vec<BitSet> = self
+            .alternatives
+            .iter()
+            .map(|(_, symbols)| self.first_of(symbols))
+            .collect();
+        let mut by_variable: Vec<Vec<usize>> = vec![vec![]; self.symbols.variable_count()];
+        for (index, (variable, _)) in self.alternatives.iter().enumerate() {
+            by_variable[*variable].push(index);
+        }
+        let mut report = ConflictReport::default();
+
+        for (i, (variable, _)) in self.alternatives.iter().enumerate() {
+            let follows = &self.follows[*variable];
+
+            for &j in by_variable[*variable].iter().filter(|j| **j > i) {
+                let (left, right) = (&alternatives[i], &alternatives[j]);
+
+                // two nullable alternatives both fill the cells of every follow
+                let mut shared = firsts[i].intersection(&firsts[j]);
+                if shared.remove(epsilon) {
+                    shared.union_with(follows);
+                }
+                if !shared.is_empty() {
+                    report.conflicts.push(Conflict {
+                        kind: ConflictKind::FirstFirst,
+                        variable: left.variable.clone(),
+                        alternatives: (left.clone(), right.clone()),
+                        lookaheads: self.symbols.ordered_tokens(&shared),
+                    });
+                }
+
+                // cells already reported for the pair are not reported again
+                for (nullable, other) in [(i, j), (j, i)] {
+                    if !firsts[nullable].contains(epsilon) {
+                        continue;
+                    }
+
+                    let mut clashing = firsts[other].intersection(follows);
+                    clashing.remove(epsilon);
+                    for id in shared.iter() {
+                        clashing.remove(id);
+                    }
+                    if !clashing.is_empty() {
+                        report.conflicts.push(Conflict {
+                            kind: ConflictKind::FirstFollow,
+                            variable: left.variable.clone(),
+                            alternatives: (
+                                alternatives[nullable].clone(),
+                                alternatives[other].clone(),
+                            ),
+                            lookaheads: self.symbols.ordered_tokens(&clashing),
+                        });
+                    }
+                }
+            }
+        }
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::conflict::ConflictKind;
+    use crate::grammar::Grammar;
+    use crate::production::Production;
+    use crate::token::{Token, EPSILON};
+
+    #[test]
+    fn test_ll1_grammar_has_no_conflicts() {
+        let mut grammar = Grammar {
+            variables: vec![
+                "E".to_string(),
+                "Z".to_string(),
+                "T".to_string(),
+                "Y".to_string(),
+                "F".to_string(),
+            ],
+            terminals: vec![
+                "+".to_string(),
+                "*".to_string(),
+                "(".to_string(),
+                "id".to_string(),
+                ")".to_string(),
+            ],
+            productions: vec![],
+            initial_symbol: "E".to_string(),
+        };
+
+        grammar.add_production(Production::new("E", "TZ".to_string()).unwrap());
+        grammar.add_production(Production::new("Z", "+TZ | £".to_string()).unwrap());
+        grammar.add_production(Production::new("T", "FY".to_string()).unwrap());
+        grammar.add_production(Production::new("Y", "*FY | £".to_string()).unwrap());
+        grammar.add_production(Production::new("F", "(E) | id".to_string()).unwrap());
+        grammar.compute_follows();
+
+        assert!(grammar.ll1_conflicts().is_ll1());
+    }
+
+    #[test]
+    fn test_first_first_conflict() {
+        let mut grammar = Grammar {
+            variables: vec!["E".to_string(), "T".to_string()],
+            terminals: vec!["+".to_string(), "id".to_string()],
+            productions: vec![],
+            initial_symbol: "E".to_string(),
+        };
+
+        grammar.add_production(Production::new("E", "E+T | T".to_string()).unwrap());
+        grammar.add_production(Production::new("T", "id".to_string()).unwrap());
+        grammar.compute_follows();
+
+        let report = grammar.ll1_conflicts();
+
+        assert_eq!(report.conflicts.len(), 1);
+        let conflict = &report.conflicts[0];
+        assert_eq!(conflict.kind, ConflictKind::FirstFirst);
+        assert_eq!(conflict.variable, "E");
+        assert_eq!(conflict.alternatives.0.to_string(), "E -> E+T");
+        assert_eq!(conflict.alternatives.1.to_string(), "E -> T");
+        assert_eq!(conflict.lookaheads, vec![Token::Terminal("id".to_string())]);
+    }
+
+    #[test]
+    fn test_first_follow_conflict() {
+        let mut grammar = Grammar {
+            variables: vec!["S".to_string(), "A".to_string()],
+            terminals: vec!["a".to_string(), "b".to_string()],
+            productions: vec![],
+            initial_symbol: "S".to_string(),
+        };
+
+        grammar.add_production(Production::new("S", "Aa".to_string()).unwrap());
+        grammar.add_production(Production::new("A", "aA | £".to_string()).unwrap());
+        grammar.compute_follows();
+
+        let report = grammar.ll1_conflicts();
+
+        assert_eq!(report.conflicts.len(), 1);
+        let conflict = &report.conflicts[0];
+        assert_eq!(conflict.kind, ConflictKind::FirstFollow);
+        assert_eq!(conflict.variable, "A");
+        assert_eq!(
+            conflict.alternatives.0.to_string(),
+            format!("A -> {}", EPSILON)
+        );
+        assert_eq!(conflict.alternatives.1.to_string(), "A -> aA");
+        assert_eq!(conflict.lookaheads, vec![Token::Terminal("a".to_string())]);
+    }
+
+    #[test]
+    fn test_two_nullable_alternatives() {
+        let mut grammar = Grammar {
+            variables: vec!["S".to_string(), "A".to_string(), "B".to_string()],
+            terminals: vec!["a".to_string(), "b".to_string()],
+            productions: vec![],
+            initial_symbol: "S".to_string(),
+        };
+
+        grammar.add_production(Production::new("S", "A | B".to_string()).unwrap());
+        grammar.add_production(Production::new("A", "a | £".to_string()).unwrap());
+        grammar.add_production(Production::new("B", "b | £".to_string()).unwrap());
+        grammar.compute_follows();
+
+        let report = grammar.ll1_conflicts();
+
+        assert_eq!(report.for_variable("S").len(), 1);
+        assert_eq!(report.conflicts[0].kind, ConflictKind::FirstFirst);
+        assert_eq!(report.conflicts[0].lookaheads, vec![Token::DollarSign]);
+    }
+
+    #[test]
+    fn test_nullable_pair_cells_reported_once() {
+        let mut grammar = Grammar {
+            variables: vec![
+                "S".to_string(),
+                "A".to_string(),
+                "B".to_string(),
+                "C".to_string(),
+            ],
+            terminals: vec!["c".to_string()],
+            productions: vec![],
+            initial_symbol: "S".to_string(),
+        };
+
+        grammar.add_production(Production::new("S", "A c".to_string()).unwrap());
+        grammar.add_production(Production::new("A", "B | C".to_string()).unwrap());
+        grammar.add_production(Production::new("B", "c | £".to_string()).unwrap());
+        grammar.add_production(Production::new("C", "£".to_string()).unwrap());
+        grammar.compute_follows();
+
+        let report = grammar.ll1_conflicts();
+        let conflicts = report.for_variable("A");
+
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].kind, ConflictKind::FirstFirst);
+        assert_eq!(
+            conflicts[0].lookaheads,
+            vec![Token::Terminal("c".to_string())]
+        );
+    }
+}
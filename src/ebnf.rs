@@ -0,0 +1,209 @@
+use crate::bnf::BnfParser;
+use crate::grammar::Grammar;
+use crate::load::{lex, read_source, source_text, LoadError, RenamedLiteral};
+use std::fmt;
+use std::path::Path;
+
+const PUNCTUATION: [&str; 13] = [
+    "->", "::=", "|", ";", "(", ")", "[", "]", "{", "}", "*", "+", "?",
+];
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Construct {
+    Group,
+    Optional,
+    Repetition,
+    OneOrMore,
+}
+
+impl fmt::Display for Construct {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Construct::Group => f.write_str("group"),
+            Construct::Optional => f.write_str("optional"),
+            Construct::Repetition => f.write_str("repetition"),
+            Construct::OneOrMore => f.write_str("one or more"),
+        }
+    }
+}
+
+/// A variable generated while desugaring, with the construct it replaces.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Helper {
+    pub variable: String,
+    pub rule: String,
+    pub construct: Construct,
+    pub text: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for Helper {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} is the {} '{}' of {} (line {}, column {})",
+            self.variable, self.construct, self.text, self.rule, self.line, self.column
+        )
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct EbnfGrammar {
+    pub grammar: Grammar,
+    pub helpers: Vec<Helper>,
+    pub literals: Vec<RenamedLiteral>,
+}
+
+impl EbnfGrammar {
+    pub fn helper(&self, variable: &str) -> Option<&Helper> {
+        self.helpers.iter().find(|h| h.variable == variable)
+    }
+
+    /// Describes a variable for diagnostics, helpers by the construct they come from.
+    pub fn describe(&self, variable: &str) -> String {
+        match self.helper(variable) {
+            Some(helper) => format!("'{}' in {}", helper.text, helper.rule),
+            None => variable.to_string(),
+        }
+    }
+
+    /// The text a terminal was written as, see `load::RenamedLiteral`.
+    pub fn source_text<'a>(&'a self, terminal: &'a str) -> &'a str {
+        source_text(&self.literals, terminal)
+    }
+}
+
+/// Reads the BNF format of `bnf::parse` extended with `{ x }` and `x*` for repetition,
+/// `[ x ]` and `x?` for optional parts, `x+` for one or more and `( a | b )` for grouping.
+/// Brackets and operators are reserved, so terminals such as `(` or `+` must be quoted.
+///
+/// Each construct becomes a fresh variable named after its rule, `E_1`, `E_2`..., repetitions
+/// are right recursive so the result stays suitable for an LL(1) table.
+pub fn parse(input: &str) -> Result<EbnfGrammar, LoadError> {
+    let lexemes = lex(input, &PUNCTUATION)?;
+    BnfParser::new(lexemes).parse_with_helpers()
+}
+
+pub fn read(path: impl AsRef<Path>) -> Result<EbnfGrammar, LoadError> {
+    parse(&read_source(path)?)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ebnf::{parse, Construct};
+    use crate::load::LoadError;
+    use crate::table::ParseTable;
+
+    fn derivations(input: &str) -> Vec<(String, String)> {
+        parse(input)
+            .unwrap()
+            .grammar
+            .productions
+            .iter()
+            .map(|p| (p.variable.clone(), p.derivation.clone()))
+            .collect()
+    }
+
+    fn pairs(expected: Vec<(&str, &str)>) -> Vec<(String, String)> {
+        expected
+            .into_iter()
+            .map(|(v, d)| (v.to_string(), d.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_repetition_and_optional() {
+        assert_eq!(
+            derivations("S -> a { ',' a } [ ';' ]"),
+            pairs(vec![
+                ("S", "a S_1 S_2"),
+                ("S_1", ", a S_1 | £"),
+                ("S_2", "; | £"),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_postfix_operators() {
+        assert_eq!(
+            derivations("S -> a* b+ c?"),
+            pairs(vec![
+                ("S", "S_1 b S_2 S_3"),
+                ("S_1", "a S_1 | £"),
+                ("S_2", "b S_2 | £"),
+                ("S_3", "c | £"),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_groups() {
+        assert_eq!(
+            derivations("S -> ( a | b ) c ( d e ) ( x | y )*"),
+            pairs(vec![
+                ("S", "S_1 c d e S_3"),
+                ("S_1", "a | b"),
+                ("S_2", "x | y"),
+                ("S_3", "S_2 S_3 | £"),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_helpers_map_back() {
+        let ebnf = parse(
+            "E -> T { ( '+' | '-' ) T }
+            T -> id | '(' E ')'",
+        )
+        .unwrap();
+
+        let helper = ebnf.helper("E_2").unwrap();
+        assert_eq!(helper.construct, Construct::Repetition);
+        assert_eq!(helper.text, "{ ( '+' | '-' ) T }");
+        assert_eq!(helper.rule, "E");
+        assert_eq!((helper.line, helper.column), (1, 8));
+        assert_eq!(ebnf.describe("E_1"), "'( '+' | '-' )' in E");
+        assert_eq!(ebnf.describe("T"), "T");
+
+        let mut grammar = ebnf.grammar;
+        grammar.compute_follows();
+        assert!(ParseTable::new(&grammar).is_ok());
+    }
+
+    #[test]
+    fn test_fresh_names_avoid_existing_symbols() {
+        assert_eq!(
+            derivations("S -> S_1 [ a ]\nS_1 -> b"),
+            pairs(vec![("S", "S_1 S_2"), ("S_2", "a | £"), ("S_1", "b")])
+        );
+    }
+
+    #[test]
+    fn test_unbalanced_brackets() {
+        assert_eq!(
+            parse("S -> { a ( b }"),
+            Err(LoadError::Syntax {
+                line: 1,
+                column: 14,
+                message: "unexpected '}' inside a rule".to_string()
+            })
+        );
+        assert_eq!(
+            parse("S -> [ a\n; T -> b"),
+            Err(LoadError::Syntax {
+                line: 1,
+                column: 6,
+                message: "'[' is never closed".to_string()
+            })
+        );
+        assert_eq!(
+            parse("S -> * a"),
+            Err(LoadError::Syntax {
+                line: 1,
+                column: 6,
+                message: "'*' must follow a symbol or a group".to_string()
+            })
+        );
+    }
+}
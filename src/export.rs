@@ -0,0 +1,162 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fmt;
+
+#[derive(Debug)]
+pub enum ExportError {
+    Json(serde_json::Error),
+    TomlSerialize(toml::ser::Error),
+    TomlDeserialize(toml::de::Error),
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportError::Json(e) => write!(f, "JSON: {}", e),
+            ExportError::TomlSerialize(e) => write!(f, "TOML: {}", e),
+            ExportError::TomlDeserialize(e) => write!(f, "TOML: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ExportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ExportError::Json(e) => Some(e),
+            ExportError::TomlSerialize(e) => Some(e),
+            ExportError::TomlDeserialize(e) => Some(e),
+        }
+    }
+}
+
+/// Serializes a grammar, a parse table or any other serializable value as pretty-printed JSON.
+pub fn to_json<T: Serialize>(value: &T) -> Result<String, ExportError> {
+    serde_json::to_string_pretty(value).map_err(ExportError::Json)
+}
+
+pub fn from_json<T: DeserializeOwned>(input: &str) -> Result<T, ExportError> {
+    serde_json::from_str(input).map_err(ExportError::Json)
+}
+
+/// Serializes a value as TOML, which needs a struct at the top level.
+pub fn to_toml<T: Serialize>(value: &T) -> Result<String, ExportError> {
+    toml::to_string_pretty(value).map_err(ExportError::TomlSerialize)
+}
+
+pub fn from_toml<T: DeserializeOwned>(input: &str) -> Result<T, ExportError> {
+    toml::from_str(input).map_err(ExportError::TomlDeserialize)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::export::{from_json, from_toml, to_json, to_toml, ExportError};
+    use crate::grammar::Grammar;
+    use crate::production::Production;
+    use crate::table::{ErrorHandler, ParseTable};
+    use crate::token::Token;
+
+    #[test]
+    fn test_grammar_round_trip() {
+        let mut grammar = Grammar {
+            variables: vec!["E".to_string(), "Z".to_string(), "T".to_string()],
+            terminals: vec!["+".to_string(), "id".to_string()],
+            productions: vec![],
+            initial_symbol: "E".to_string(),
+        };
+
+        grammar.add_production(Production::new("E", "T Z".to_string()).unwrap());
+        grammar.add_production(Production::new("Z", "+ T Z | £".to_string()).unwrap());
+        grammar.add_production(Production::new("T", "id".to_string()).unwrap());
+        grammar.compute_follows();
+
+        let json = to_json(&grammar).unwrap();
+        assert!(json.contains("\"kind\": \"dollar_sign\""));
+        assert_eq!(to_json(&grammar).unwrap(), json);
+        assert_eq!(from_json::<Grammar>(&json).unwrap(), grammar);
+
+        let toml = to_toml(&grammar).unwrap();
+        assert!(toml.contains("initial_symbol = \"E\""));
+        assert_eq!(from_toml::<Grammar>(&toml).unwrap(), grammar);
+    }
+
+    #[test]
+    fn test_sets_follow_declaration_order() {
+        let mut grammar = Grammar {
+            variables: vec!["E".to_string(), "Z".to_string(), "T".to_string()],
+            terminals: vec!["+".to_string(), "id".to_string()],
+            productions: vec![],
+            initial_symbol: "E".to_string(),
+        };
+
+        grammar.add_production(Production::new("E", "T Z".to_string()).unwrap());
+        grammar.add_production(Production::new("Z", "+ T Z | £".to_string()).unwrap());
+        grammar.add_production(Production::new("T", "id".to_string()).unwrap());
+        grammar.compute_follows();
+
+        let value: serde_json::Value = serde_json::from_str(&to_json(&grammar).unwrap()).unwrap();
+
+        // follows of T: +, then $
+        assert_eq!(
+            value["productions"][2]["follows"],
+            serde_json::json!([
+                { "kind": "terminal", "name": "+" },
+                { "kind": "dollar_sign" }
+            ])
+        );
+        assert_eq!(
+            value["productions"][1]["firsts"],
+            serde_json::json!([{ "kind": "terminal", "name": "+" }, { "kind": "epsilon" }])
+        );
+    }
+
+    #[test]
+    fn test_parse_table_round_trip() {
+        let mut grammar = Grammar {
+            variables: vec!["E".to_string(), "Z".to_string(), "T".to_string()],
+            terminals: vec!["+".to_string(), "id".to_string()],
+            productions: vec![],
+            initial_symbol: "E".to_string(),
+        };
+
+        grammar.add_production(Production::new("E", "T Z".to_string()).unwrap());
+        grammar.add_production(Production::new("Z", "+ T Z | £".to_string()).unwrap());
+        grammar.add_production(Production::new("T", "id".to_string()).unwrap());
+        grammar.compute_follows();
+
+        let mut table = ParseTable::new(&grammar).unwrap();
+        table
+            .set_error_handler(
+                "T",
+                Token::Terminal("+".to_string()),
+                ErrorHandler::Insert(Token::Terminal("id".to_string())),
+            )
+            .unwrap();
+
+        let json = to_json(&table).unwrap();
+        assert_eq!(from_json::<ParseTable>(&json).unwrap(), table);
+        assert_eq!(to_json(&table).unwrap(), json);
+
+        let toml = to_toml(&table).unwrap();
+        assert_eq!(from_toml::<ParseTable>(&toml).unwrap(), table);
+    }
+
+    #[test]
+    fn test_fixture_without_analysis() {
+        let grammar: Grammar = from_json(
+            r#"{
+                "variables": ["S"],
+                "terminals": ["a"],
+                "productions": [{ "variable": "S", "derivation": "a S | £" }],
+                "initial_symbol": "S"
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(grammar.productions[0].derivation, "a S | £");
+        assert!(grammar.productions[0].firsts.is_empty());
+        assert!(matches!(
+            from_json::<Grammar>("{}"),
+            Err(ExportError::Json(_))
+        ));
+    }
+}
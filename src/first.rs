@@ -1,69 +1,138 @@
 use super::grammar::Grammar;
-use super::production::Production;
-use crate::token::{Token, TokenProcessor};
-use std::collections::HashSet;
-
-impl Production {
-    fn process_variable(
-        variable: char,
-        grammar: &Grammar,
-        firsts: &mut HashSet<Token>,
-        production: &Production,
-    ) -> bool {
-        if let Some(p) = grammar.get_production_by_var(variable) {
-            let variable_firsts = Self::fetch_firsts(p, grammar);
-            // only continue processing derivation if the variable firsts contains an epsilon
-            let should_continue = variable_firsts.contains(&Token::Epsilon);
-
-            for token in variable_firsts {
-                match token {
-                    // rule 3.a
-                    Token::Epsilon => {
-                        production.ends_with_variable(variable) && firsts.insert(token)
-                    }
-                    _ => firsts.insert(token),
-                };
+use crate::bitset::BitSet;
+use crate::nullable::nullable_variables;
+use crate::symbol::{Symbol, SymbolTable};
+use crate::token::Token;
+use std::collections::{HashSet, VecDeque};
+
+/// Firsts of a sequence of symbols given the firsts known so far for each variable,
+/// epsilon is only present when the whole sequence is nullable.
+pub(crate) fn sequence_first(
+    symbols: &[Symbol],
+    firsts: &[BitSet],
+    nullable: &BitSet,
+    table: &SymbolTable,
+) -> BitSet {
+    let mut result = table.terminal_set();
+
+    for symbol in symbols {
+        match symbol {
+            Symbol::Epsilon => continue,
+            Symbol::Terminal(id) => {
+                result.insert(*id);
+                return result;
             }
+            Symbol::Variable(id) => {
+                result.union_without(&firsts[*id], table.epsilon());
 
-            return should_continue;
+                // only continue processing derivation if the variable derives epsilon
+                if !nullable.contains(*id) {
+                    return result;
+                }
+            }
         }
-
-        false
     }
 
-    fn fetch_firsts(production: &Production, grammar: &Grammar) -> HashSet<Token> {
-        let mut firsts: HashSet<Token> = HashSet::new();
-        let processor = TokenProcessor::new(grammar);
-
-        for slice in processor.process_derivation(&production.derivation) {
-            for token in slice.tokens {
-                let should_continue = match token {
-                    Token::Variable(ch) => {
-                        Self::process_variable(ch, grammar, &mut firsts, &production)
-                    }
-                    _ => {
-                        firsts.insert(token);
-                        false
-                    }
-                };
+    result.insert(table.epsilon());
+    result
+}
 
-                if !should_continue {
-                    break;
+/// Firsts of every variable, indexed by variable id, computed with a worklist over the
+/// alternatives that runs until no set changes, so left-recursive and mutually recursive
+/// variables terminate with their complete sets.
+pub(crate) fn first_sets(
+    table: &SymbolTable,
+    alternatives: &[(usize, Vec<Symbol>)],
+    nullable: &BitSet,
+) -> Vec<BitSet> {
+    // alternatives that must be revisited when the firsts of a variable grow
+    let mut dependents: Vec<Vec<usize>> = vec![vec![]; table.variable_count()];
+    for (index, (_, symbols)) in alternatives.iter().enumerate() {
+        for symbol in symbols.iter() {
+            if let Symbol::Variable(id) = symbol {
+                if dependents[*id].last() != Some(&index) {
+                    dependents[*id].push(index);
                 }
             }
         }
+    }
+
+    let mut firsts: Vec<BitSet> = vec![table.terminal_set(); table.variable_count()];
+    let mut worklist: VecDeque<usize> = (0..alternatives.len()).collect();
+    let mut queued = vec![true; alternatives.len()];
+
+    while let Some(index) = worklist.pop_front() {
+        queued[index] = false;
+        let (variable, symbols) = &alternatives[index];
 
-        firsts
+        let computed = sequence_first(symbols, &firsts, nullable, table);
+        if !firsts[*variable].union_with(&computed) {
+            continue;
+        }
+
+        for &dependent in dependents[*variable].iter() {
+            if !queued[dependent] {
+                queued[dependent] = true;
+                worklist.push_back(dependent);
+            }
+        }
     }
+
+    firsts
 }
 
 impl Grammar {
     pub fn compute_firsts(&mut self) {
-        let immut_self = self.clone();
+        let table = SymbolTable::new(self);
+        let alternatives = table.alternatives(self);
+        let nullable = nullable_variables(&table, &alternatives);
+        let firsts = first_sets(&table, &alternatives, &nullable);
 
         for p in self.productions_iter_mut() {
-            p.set_firsts(Production::fetch_firsts(p, &immut_self))
+            let id = table.variable_id(&p.variable).unwrap();
+            p.set_firsts(table.tokens(&firsts[id]))
+        }
+    }
+
+    /// Computes the firsts of a sequence of tokens, such as a `DerivationTokenSlice` or the
+    /// tail of one, with epsilon only present when the whole sequence is nullable.
+    /// Relies on the firsts stored by `compute_firsts`.
+    pub fn first_of(&self, tokens: &[Token]) -> HashSet<Token> {
+        let mut result: HashSet<Token> = HashSet::new();
+
+        for token in tokens {
+            match token {
+                Token::Epsilon => continue,
+                Token::Variable(name) => {
+                    let mut nullable = false;
+                    let variable_firsts = self
+                        .productions
+                        .iter()
+                        .filter(|p| p.variable == *name)
+                        .flat_map(|p| p.firsts.iter());
+
+                    for first in variable_firsts {
+                        match first {
+                            Token::Epsilon => nullable = true,
+                            _ => {
+                                result.insert(first.clone());
+                            }
+                        }
+                    }
+
+                    if !nullable {
+                        return result;
+                    }
+                }
+                _ => {
+                    result.insert(token.clone());
+                    return result;
+                }
+            }
         }
+
+        result.insert(Token::Epsilon);
+        result
     }
 }
 
@@ -89,21 +158,21 @@ mod test {
     #[test]
     fn test_firsts_simple() {
         let mut grammar = Grammar {
-            variables: vec!['S', 'A', 'B'],
+            variables: vec!["S".to_string(), "A".to_string(), "B".to_string()],
             terminals: vec!["a".to_string(), "b".to_string(), "c".to_string()],
             productions: vec![],
-            initial_symbol: 'S',
+            initial_symbol: "S".to_string(),
         };
 
-        grammar.add_production(Production::new('S', "AB".to_string()).unwrap());
-        grammar.add_production(Production::new('A', "aA | a".to_string()).unwrap());
-        grammar.add_production(Production::new('B', "bB | c".to_string()).unwrap());
+        grammar.add_production(Production::new("S", "AB".to_string()).unwrap());
+        grammar.add_production(Production::new("A", "aA | a".to_string()).unwrap());
+        grammar.add_production(Production::new("B", "bB | c".to_string()).unwrap());
 
         grammar.compute_firsts();
 
-        let s = grammar.get_production_by_var('S').unwrap();
-        let a = grammar.get_production_by_var('A').unwrap();
-        let b = grammar.get_production_by_var('B').unwrap();
+        let s = grammar.get_production_by_var("S").unwrap();
+        let a = grammar.get_production_by_var("A").unwrap();
+        let b = grammar.get_production_by_var("B").unwrap();
         let set_s: HashSet<_> = hash_from_vec(vec!["a"]);
         let set_b: HashSet<_> = hash_from_vec(vec!["b", "c"]);
 
@@ -115,21 +184,21 @@ mod test {
     #[test]
     fn test_firsts_epsilon() {
         let mut grammar = Grammar {
-            variables: vec!['S', 'A', 'B'],
+            variables: vec!["S".to_string(), "A".to_string(), "B".to_string()],
             terminals: vec!["a".to_string(), "b".to_string(), "c".to_string()],
             productions: vec![],
-            initial_symbol: 'S',
+            initial_symbol: "S".to_string(),
         };
 
-        grammar.add_production(Production::new('S', "AB".to_string()).unwrap());
-        grammar.add_production(Production::new('A', "aA | a | £".to_string()).unwrap());
-        grammar.add_production(Production::new('B', "bB | c".to_string()).unwrap());
+        grammar.add_production(Production::new("S", "AB".to_string()).unwrap());
+        grammar.add_production(Production::new("A", "aA | a | £".to_string()).unwrap());
+        grammar.add_production(Production::new("B", "bB | c".to_string()).unwrap());
 
         grammar.compute_firsts();
 
-        let s = grammar.get_production_by_var('S').unwrap();
-        let a = grammar.get_production_by_var('A').unwrap();
-        let b = grammar.get_production_by_var('B').unwrap();
+        let s = grammar.get_production_by_var("S").unwrap();
+        let a = grammar.get_production_by_var("A").unwrap();
+        let b = grammar.get_production_by_var("B").unwrap();
         let set_s: HashSet<_> = hash_from_vec(vec!["a", "b", "c"]);
         let set_a: HashSet<_> = hash_from_vec(vec!["a", EPSILON]);
         let set_b: HashSet<_> = hash_from_vec(vec!["b", "c"]);
@@ -142,21 +211,21 @@ mod test {
     #[test]
     fn test_firsts_epsilon_2() {
         let mut grammar = Grammar {
-            variables: vec!['S', 'A', 'B'],
+            variables: vec!["S".to_string(), "A".to_string(), "B".to_string()],
             terminals: vec!["a".to_string(), "b".to_string(), "c".to_string()],
             productions: vec![],
-            initial_symbol: 'S',
+            initial_symbol: "S".to_string(),
         };
 
-        grammar.add_production(Production::new('S', "AB".to_string()).unwrap());
-        grammar.add_production(Production::new('A', "aA | a | £".to_string()).unwrap());
-        grammar.add_production(Production::new('B', "bB | c | £".to_string()).unwrap());
+        grammar.add_production(Production::new("S", "AB".to_string()).unwrap());
+        grammar.add_production(Production::new("A", "aA | a | £".to_string()).unwrap());
+        grammar.add_production(Production::new("B", "bB | c | £".to_string()).unwrap());
 
         grammar.compute_firsts();
 
-        let s = grammar.get_production_by_var('S').unwrap();
-        let a = grammar.get_production_by_var('A').unwrap();
-        let b = grammar.get_production_by_var('B').unwrap();
+        let s = grammar.get_production_by_var("S").unwrap();
+        let a = grammar.get_production_by_var("A").unwrap();
+        let b = grammar.get_production_by_var("B").unwrap();
         let set_s: HashSet<_> = hash_from_vec(vec!["a", "b", "c", EPSILON]);
         let set_a: HashSet<_> = hash_from_vec(vec!["a", EPSILON]);
         let set_b: HashSet<_> = hash_from_vec(vec!["b", "c", EPSILON]);
@@ -169,7 +238,13 @@ mod test {
     #[test]
     fn test_first_complex_grammar() {
         let mut grammar = Grammar {
-            variables: vec!['E', 'Z', 'T', 'Y', 'F'],
+            variables: vec![
+                "E".to_string(),
+                "Z".to_string(),
+                "T".to_string(),
+                "Y".to_string(),
+                "F".to_string(),
+            ],
             terminals: vec![
                 "+".to_string(),
                 "*".to_string(),
@@ -178,22 +253,22 @@ mod test {
                 ")".to_string(),
             ],
             productions: vec![],
-            initial_symbol: 'S',
+            initial_symbol: "S".to_string(),
         };
 
-        grammar.add_production(Production::new('E', "TZ".to_string()).unwrap());
-        grammar.add_production(Production::new('Z', "+TZ | £".to_string()).unwrap());
-        grammar.add_production(Production::new('T', "FY".to_string()).unwrap());
-        grammar.add_production(Production::new('Y', "*FY | £".to_string()).unwrap());
-        grammar.add_production(Production::new('F', "(E) | id".to_string()).unwrap());
+        grammar.add_production(Production::new("E", "TZ".to_string()).unwrap());
+        grammar.add_production(Production::new("Z", "+TZ | £".to_string()).unwrap());
+        grammar.add_production(Production::new("T", "FY".to_string()).unwrap());
+        grammar.add_production(Production::new("Y", "*FY | £".to_string()).unwrap());
+        grammar.add_production(Production::new("F", "(E) | id".to_string()).unwrap());
 
         grammar.compute_firsts();
 
-        let e = grammar.get_production_by_var('E').unwrap();
-        let z = grammar.get_production_by_var('Z').unwrap();
-        let t = grammar.get_production_by_var('T').unwrap();
-        let y = grammar.get_production_by_var('Y').unwrap();
-        let f = grammar.get_production_by_var('F').unwrap();
+        let e = grammar.get_production_by_var("E").unwrap();
+        let z = grammar.get_production_by_var("Z").unwrap();
+        let t = grammar.get_production_by_var("T").unwrap();
+        let y = grammar.get_production_by_var("Y").unwrap();
+        let f = grammar.get_production_by_var("F").unwrap();
 
         let set_f = hash_from_vec(vec!["(", "id"]);
         let set_z = hash_from_vec(vec!["+", EPSILON]);
@@ -205,4 +280,122 @@ mod test {
         assert_eq!(y.firsts, set_y, "Testing variable Y");
         assert_eq!(f.firsts, set_f, "Testing variable F");
     }
+
+    #[test]
+    fn test_firsts_left_recursive() {
+        let mut grammar = Grammar {
+            variables: vec!["E".to_string(), "T".to_string()],
+            terminals: vec!["+".to_string(), "id".to_string()],
+            productions: vec![],
+            initial_symbol: "E".to_string(),
+        };
+
+        grammar.add_production(Production::new("E", "E+T | T".to_string()).unwrap());
+        grammar.add_production(Production::new("T", "id".to_string()).unwrap());
+
+        grammar.compute_firsts();
+
+        let e = grammar.get_production_by_var("E").unwrap();
+        let t = grammar.get_production_by_var("T").unwrap();
+        let set_id = hash_from_vec(vec!["id"]);
+
+        assert_eq!(e.firsts, set_id, "Testing variable E");
+        assert_eq!(t.firsts, set_id, "Testing variable T");
+    }
+
+    #[test]
+    fn test_firsts_mutually_recursive() {
+        let mut grammar = Grammar {
+            variables: vec!["A".to_string(), "B".to_string()],
+            terminals: vec!["x".to_string(), "y".to_string()],
+            productions: vec![],
+            initial_symbol: "A".to_string(),
+        };
+
+        grammar.add_production(Production::new("A", "Bx".to_string()).unwrap());
+        grammar.add_production(Production::new("B", "Ay | £".to_string()).unwrap());
+
+        grammar.compute_firsts();
+
+        let a = grammar.get_production_by_var("A").unwrap();
+        let b = grammar.get_production_by_var("B").unwrap();
+        let set_a = hash_from_vec(vec!["x"]);
+        let set_b = hash_from_vec(vec!["x", EPSILON]);
+
+        assert_eq!(a.firsts, set_a, "Testing variable A");
+        assert_eq!(b.firsts, set_b, "Testing variable B");
+    }
+
+    #[test]
+    fn test_firsts_nullable_chain() {
+        let mut grammar = Grammar {
+            variables: vec!["S".to_string(), "A".to_string(), "B".to_string()],
+            terminals: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            productions: vec![],
+            initial_symbol: "S".to_string(),
+        };
+
+        grammar.add_production(Production::new("S", "ABc".to_string()).unwrap());
+        grammar.add_production(Production::new("A", "Ba | £".to_string()).unwrap());
+        grammar.add_production(Production::new("B", "bS | £".to_string()).unwrap());
+
+        grammar.compute_firsts();
+
+        let s = grammar.get_production_by_var("S").unwrap();
+        let a = grammar.get_production_by_var("A").unwrap();
+        let set_s = hash_from_vec(vec!["a", "b", "c"]);
+        let set_a = hash_from_vec(vec!["a", "b", EPSILON]);
+
+        assert_eq!(s.firsts, set_s, "Testing variable S");
+        assert_eq!(a.firsts, set_a, "Testing variable A");
+    }
+
+    #[test]
+    fn test_first_of_sequence() {
+        let mut grammar = Grammar {
+            variables: vec![
+                "E".to_string(),
+                "Z".to_string(),
+                "T".to_string(),
+                "Y".to_string(),
+                "F".to_string(),
+            ],
+            terminals: vec![
+                "+".to_string(),
+                "*".to_string(),
+                "(".to_string(),
+                "id".to_string(),
+                ")".to_string(),
+            ],
+            productions: vec![],
+            initial_symbol: "E".to_string(),
+        };
+
+        grammar.add_production(Production::new("E", "TZ".to_string()).unwrap());
+        grammar.add_production(Production::new("Z", "+TZ | £".to_string()).unwrap());
+        grammar.add_production(Production::new("T", "FY".to_string()).unwrap());
+        grammar.add_production(Production::new("Y", "*FY | £".to_string()).unwrap());
+        grammar.add_production(Production::new("F", "(E) | id".to_string()).unwrap());
+
+        grammar.compute_firsts();
+
+        let close = Token::Terminal(")".to_string());
+
+        assert_eq!(
+            grammar.first_of(&[Token::Variable("Z".to_string()), close.clone()]),
+            hash_from_vec(vec!["+", ")"])
+        );
+        assert_eq!(
+            grammar.first_of(&[
+                Token::Variable("Y".to_string()),
+                Token::Variable("Z".to_string())
+            ]),
+            hash_from_vec(vec!["*", "+", EPSILON])
+        );
+        assert_eq!(
+            grammar.first_of(&[close, Token::Variable("E".to_string())]),
+            hash_from_vec(vec![")"])
+        );
+        assert_eq!(grammar.first_of(&[]), hash_from_vec(vec![EPSILON]));
+    }
 }
@@ -1,179 +1,113 @@
-use crate::grammar::Grammar;
-use crate::production::Production;
-use crate::token::{Token, TokenProcessor};
+use crate::analysis::Analysis;
+use crate::bitset::BitSet;
+use crate::grammar::{Grammar, GrammarError};
+use crate::symbol::{Symbol, SymbolTable};
+use crate::token::Token;
 use std::collections::HashSet;
 
-#[derive(Debug, Clone)]
-struct Item {
-    variable: char,
-    token: Token,
-}
-
-impl Item {
-    fn new(variable: char, token: Token) -> Item {
-        Item { variable, token }
+/// Follows of every variable, indexed by variable id. Placeholders stand for the follows of
+/// another variable and are resolved repeatedly until no set grows, so cyclic dependencies
+/// between follows end up complete.
+pub(crate) fn follow_sets(
+    table: &SymbolTable,
+    alternatives: &[(usize, Vec<Symbol>)],
+    nullable: &BitSet,
+    firsts: &[BitSet],
+    initial_symbol: Option<usize>,
+) -> Vec<BitSet> {
+    let mut follows: Vec<BitSet> = vec![table.terminal_set(); table.variable_count()];
+    // (variable, source): the follows of source are included in the follows of variable
+    let mut placeholders: Vec<(usize, usize)> = vec![];
+
+    // rule 1
+    if let Some(id) = initial_symbol {
+        follows[id].insert(table.dollar_sign());
     }
-}
 
-impl Production {
-    fn process_follow_variable(
-        variable: char,
-        grammar: &Grammar,
-        next_token: Option<&Token>,
-    ) -> Vec<Item> {
-        let mut tokens: Vec<Item> = vec![];
-
-        if let Some(token) = next_token {
-            match token {
-                Token::Variable(ch) => {
-                    for token in Self::process_follow_rule2(ch.clone(), grammar) {
-                        tokens.push(Item::new(variable, token))
-                    }
+    for (variable, symbols) in alternatives.iter() {
+        // firsts of the suffix after the current symbol, built from right to left
+        let mut trailer = table.terminal_set();
+        let mut trailer_nullable = true;
+
+        for symbol in symbols.iter().rev() {
+            match symbol {
+                Symbol::Epsilon => continue,
+                Symbol::Terminal(id) => {
+                    trailer = table.terminal_set();
+                    trailer.insert(*id);
+                    trailer_nullable = false;
                 }
-                Token::Terminal(_) => tokens.push(Item::new(variable, token.clone())),
-                _ => (),
-            }
-        }
-
-        return tokens;
-    }
-
-    fn process_follow_rule2(variable: char, grammar: &Grammar) -> HashSet<Token> {
-        let prod = grammar.get_production_by_var(variable).unwrap();
-
-        prod.firsts
-            .iter()
-            .filter_map(|t| {
-                if !t.eq(&Token::Epsilon) {
-                    return Some(t.clone());
-                }
-
-                None
-            })
-            .collect()
-    }
+                Symbol::Variable(id) => {
+                    // rule 2
+                    follows[*id].union_with(&trailer);
 
-    fn process_follow_rule3(next_token: Option<&Token>, grammar: &Grammar) -> bool {
-        if let Some(token) = next_token {
-            match token {
-                Token::Variable(ch) => {
-                    let prod = grammar.get_production_by_var(ch.clone()).unwrap();
+                    // rule 3, the whole suffix after the variable can derive epsilon
+                    if trailer_nullable && id != variable {
+                        placeholders.push((*id, *variable));
+                    }
 
-                    return prod.firsts.contains(&Token::Epsilon);
+                    if nullable.contains(*id) {
+                        trailer.union_without(&firsts[*id], table.epsilon());
+                    } else {
+                        trailer = table.terminal_set();
+                        trailer.union_without(&firsts[*id], table.epsilon());
+                        trailer_nullable = false;
+                    }
                 }
-                _ => (),
             }
         }
-
-        false
     }
 
-    fn fetch_follows(
-        production: &Production,
-        grammar: &Grammar,
-        token_processor: &TokenProcessor,
-    ) -> Vec<Item> {
-        let mut tokens: Vec<Item> = vec![];
+    placeholders.sort_unstable();
+    placeholders.dedup();
 
-        // 1 regra
-        if grammar.production_is_initial(production) {
-            tokens.push(Item::new(production.variable, Token::DollarSign));
-        }
+    let mut changed = true;
+    while changed {
+        changed = false;
 
-        let derivations = token_processor.process_derivation(&production.derivation);
-
-        for slice in derivations {
-            let slice_size = slice.tokens.len();
-
-            for (index, token) in slice.tokens.iter().enumerate() {
-                match token {
-                    Token::Variable(ch) => {
-                        let var_tokens = Self::process_follow_variable(
-                            ch.clone(),
-                            grammar,
-                            slice.tokens.get(index + 1),
-                        );
-                        tokens = [tokens, var_tokens].concat();
-
-                        // check rule 3
-                        if index + 1 == slice_size {
-                            tokens.push(Item::new(
-                                ch.clone(),
-                                Token::Placeholder(production.variable),
-                            ))
-                        }
-
-                        // check rule 3 part 2
-                        if index + 2 == slice_size {
-                            if Self::process_follow_rule3(slice.tokens.get(index + 1), grammar) {
-                                tokens.push(Item::new(
-                                    ch.clone(),
-                                    Token::Placeholder(production.variable),
-                                ))
-                            }
-                        }
-                    }
-                    _ => {}
-                }
-            }
+        for (variable, source) in placeholders.iter() {
+            let inherited = follows[*source].clone();
+            changed |= follows[*variable].union_with(&inherited);
         }
-
-        tokens
     }
+
+    follows
 }
 
 impl Grammar {
+    /// Computes the firsts and the follows of every production.
     pub fn compute_follows(&mut self) {
-        self.compute_firsts();
+        let analysis = Analysis::new(self);
+        self.store_sets(&analysis);
+    }
 
-        let immut_self = self.clone();
-        let processor = TokenProcessor::new(&immut_self);
-        let tokens: Vec<Item> = self
-            .productions_iter_mut()
-            .map(|p| Production::fetch_follows(p, &immut_self, &processor))
-            .flatten()
-            .collect();
+    /// Like `compute_follows`, failing on derivation text that is no declared symbol.
+    pub fn compute_follows_strict(&mut self) -> Result<(), GrammarError> {
+        let analysis = Analysis::strict(self)?;
+        self.store_sets(&analysis);
 
-        tokens
-            .iter()
-            .filter(|item| match item.token {
-                Token::Placeholder(_) => false,
-                _ => true,
-            })
-            .for_each(|item| {
-                self.get_mut_production_by_var(item.variable)
-                    .unwrap()
-                    .follows
-                    .insert(item.token.clone());
-            });
-
-        // clear placeholders
-        tokens
+        Ok(())
+    }
+
+    fn store_sets(&mut self, analysis: &Analysis) {
+        for p in self.productions_iter_mut() {
+            p.set_firsts(analysis.firsts_of(&p.variable));
+            p.set_follows(analysis.follows_of(&p.variable));
+        }
+    }
+
+    pub fn follows_of(&self, variable: &str) -> HashSet<Token> {
+        self.productions
             .iter()
-            .filter(|item| match item.token {
-                Token::Placeholder(_) => true,
-                _ => false,
-            })
-            .for_each(|item| {
-                let mut cl = self.clone();
-                let item_prod = self.get_mut_production_by_var(item.variable).unwrap();
-
-                match item.token {
-                    Token::Placeholder(ch) => {
-                        let placeholder_follows = cl.get_mut_production_by_var(ch).unwrap();
-                        for f in placeholder_follows.follows.iter() {
-                            item_prod.follows.insert(f.clone());
-                        }
-                    }
-                    _ => (),
-                }
-            });
+            .filter(|p| p.variable == variable)
+            .flat_map(|p| p.follows.iter().cloned())
+            .collect()
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::grammar::Grammar;
+    use crate::grammar::{Grammar, GrammarError};
     use crate::production::Production;
     use crate::token::{Token, DOLLAR_SIGN, EPSILON};
     use std::collections::HashSet;
@@ -191,7 +125,13 @@ mod test {
     #[test]
     fn test_follow() {
         let mut grammar = Grammar {
-            variables: vec!['E', 'Z', 'T', 'Y', 'F'],
+            variables: vec![
+                "E".to_string(),
+                "Z".to_string(),
+                "T".to_string(),
+                "Y".to_string(),
+                "F".to_string(),
+            ],
             terminals: vec![
                 "+".to_string(),
                 "*".to_string(),
@@ -200,22 +140,22 @@ mod test {
                 ")".to_string(),
             ],
             productions: vec![],
-            initial_symbol: 'E',
+            initial_symbol: "E".to_string(),
         };
 
-        grammar.add_production(Production::new('E', "TZ".to_string()).unwrap());
-        grammar.add_production(Production::new('Z', "+TZ | £".to_string()).unwrap());
-        grammar.add_production(Production::new('T', "FY".to_string()).unwrap());
-        grammar.add_production(Production::new('Y', "*FY | £".to_string()).unwrap());
-        grammar.add_production(Production::new('F', "(E) | id".to_string()).unwrap());
+        grammar.add_production(Production::new("E", "TZ".to_string()).unwrap());
+        grammar.add_production(Production::new("Z", "+TZ | £".to_string()).unwrap());
+        grammar.add_production(Production::new("T", "FY".to_string()).unwrap());
+        grammar.add_production(Production::new("Y", "*FY | £".to_string()).unwrap());
+        grammar.add_production(Production::new("F", "(E) | id".to_string()).unwrap());
 
         grammar.compute_follows();
 
-        let e = grammar.get_production_by_var('E').unwrap();
-        let z = grammar.get_production_by_var('Z').unwrap();
-        let t = grammar.get_production_by_var('T').unwrap();
-        let y = grammar.get_production_by_var('Y').unwrap();
-        let f = grammar.get_production_by_var('F').unwrap();
+        let e = grammar.get_production_by_var("E").unwrap();
+        let z = grammar.get_production_by_var("Z").unwrap();
+        let t = grammar.get_production_by_var("T").unwrap();
+        let y = grammar.get_production_by_var("Y").unwrap();
+        let f = grammar.get_production_by_var("F").unwrap();
 
         let set_e = hash_from_vec(vec![DOLLAR_SIGN, ")"]);
         let set_t = hash_from_vec(vec!["+", ")", DOLLAR_SIGN]);
@@ -231,7 +171,7 @@ mod test {
     #[test]
     fn test_follow2() {
         let mut grammar = Grammar {
-            variables: vec!['S', 'B', 'C'],
+            variables: vec!["S".to_string(), "B".to_string(), "C".to_string()],
             terminals: vec![
                 "a".to_string(),
                 "b".to_string(),
@@ -239,18 +179,18 @@ mod test {
                 "d".to_string(),
             ],
             productions: vec![],
-            initial_symbol: 'S',
+            initial_symbol: "S".to_string(),
         };
 
-        grammar.add_production(Production::new('S', "Bb | Cd".to_string()).unwrap());
-        grammar.add_production(Production::new('B', "aB | £".to_string()).unwrap());
-        grammar.add_production(Production::new('C', "cC | £".to_string()).unwrap());
+        grammar.add_production(Production::new("S", "Bb | Cd".to_string()).unwrap());
+        grammar.add_production(Production::new("B", "aB | £".to_string()).unwrap());
+        grammar.add_production(Production::new("C", "cC | £".to_string()).unwrap());
 
         grammar.compute_follows();
 
-        let s = grammar.get_production_by_var('S').unwrap();
-        let b = grammar.get_production_by_var('B').unwrap();
-        let c = grammar.get_production_by_var('C').unwrap();
+        let s = grammar.get_production_by_var("S").unwrap();
+        let b = grammar.get_production_by_var("B").unwrap();
+        let c = grammar.get_production_by_var("C").unwrap();
 
         let set_s = hash_from_vec(vec![DOLLAR_SIGN]);
         let set_b = hash_from_vec(vec!["b"]);
@@ -260,4 +200,181 @@ mod test {
         assert_eq!(b.follows, set_b, "Testing variable B");
         assert_eq!(c.follows, set_c, "Testing variable C");
     }
+
+    #[test]
+    fn test_follow_cyclic_dependencies() {
+        let mut grammar = Grammar {
+            variables: vec![
+                "S".to_string(),
+                "A".to_string(),
+                "B".to_string(),
+                "C".to_string(),
+            ],
+            terminals: vec![
+                "w".to_string(),
+                "x".to_string(),
+                "y".to_string(),
+                "z".to_string(),
+            ],
+            productions: vec![],
+            initial_symbol: "S".to_string(),
+        };
+
+        // declared in reverse so that every follow depends on one that is not resolved yet
+        grammar.add_production(Production::new("C", "zA".to_string()).unwrap());
+        grammar.add_production(Production::new("B", "yC".to_string()).unwrap());
+        grammar.add_production(Production::new("A", "xB | £".to_string()).unwrap());
+        grammar.add_production(Production::new("S", "Aw".to_string()).unwrap());
+
+        grammar.compute_follows();
+
+        let set_s = hash_from_vec(vec![DOLLAR_SIGN]);
+        let set_w = hash_from_vec(vec!["w"]);
+
+        assert_eq!(
+            grammar.get_production_by_var("S").unwrap().follows,
+            set_s,
+            "Testing variable S"
+        );
+        assert_eq!(
+            grammar.get_production_by_var("A").unwrap().follows,
+            set_w,
+            "Testing variable A"
+        );
+        assert_eq!(
+            grammar.get_production_by_var("B").unwrap().follows,
+            set_w,
+            "Testing variable B"
+        );
+        assert_eq!(
+            grammar.get_production_by_var("C").unwrap().follows,
+            set_w,
+            "Testing variable C"
+        );
+    }
+
+    #[test]
+    fn test_follow_long_nullable_suffix() {
+        let mut grammar = Grammar {
+            variables: vec![
+                "S".to_string(),
+                "A".to_string(),
+                "B".to_string(),
+                "C".to_string(),
+                "D".to_string(),
+            ],
+            terminals: vec![
+                "b".to_string(),
+                "c".to_string(),
+                "d".to_string(),
+                "x".to_string(),
+                "y".to_string(),
+            ],
+            productions: vec![],
+            initial_symbol: "S".to_string(),
+        };
+
+        grammar.add_production(Production::new("S", "Ab".to_string()).unwrap());
+        grammar.add_production(Production::new("A", "xBCD".to_string()).unwrap());
+        grammar.add_production(Production::new("B", "y".to_string()).unwrap());
+        grammar.add_production(Production::new("C", "c | £".to_string()).unwrap());
+        grammar.add_production(Production::new("D", "d | £".to_string()).unwrap());
+
+        grammar.compute_follows();
+
+        let b = grammar.get_production_by_var("B").unwrap();
+        let c = grammar.get_production_by_var("C").unwrap();
+        let d = grammar.get_production_by_var("D").unwrap();
+
+        let set_b = hash_from_vec(vec!["b", "c", "d"]);
+        let set_c = hash_from_vec(vec!["b", "d"]);
+        let set_d = hash_from_vec(vec!["b"]);
+
+        assert_eq!(b.follows, set_b, "Testing variable B");
+        assert_eq!(c.follows, set_c, "Testing variable C");
+        assert_eq!(d.follows, set_d, "Testing variable D");
+    }
+
+    #[test]
+    fn test_follow_left_recursive() {
+        let mut grammar = Grammar {
+            variables: vec!["E".to_string(), "T".to_string()],
+            terminals: vec!["+".to_string(), "id".to_string()],
+            productions: vec![],
+            initial_symbol: "E".to_string(),
+        };
+
+        grammar.add_production(Production::new("E", "E+T | T".to_string()).unwrap());
+        grammar.add_production(Production::new("T", "id".to_string()).unwrap());
+
+        grammar.compute_follows();
+
+        let set_follows = hash_from_vec(vec!["+", DOLLAR_SIGN]);
+
+        assert_eq!(
+            grammar.get_production_by_var("E").unwrap().follows,
+            set_follows,
+            "Testing variable E"
+        );
+        assert_eq!(
+            grammar.get_production_by_var("T").unwrap().follows,
+            set_follows,
+            "Testing variable T"
+        );
+    }
+
+    #[test]
+    fn test_follow_named_variables() {
+        let mut grammar = Grammar {
+            variables: vec![
+                "expr".to_string(),
+                "expr_tail".to_string(),
+                "term".to_string(),
+            ],
+            terminals: vec!["+".to_string(), "id".to_string()],
+            productions: vec![],
+            initial_symbol: "expr".to_string(),
+        };
+
+        grammar.add_production(Production::new("expr", "term expr_tail".to_string()).unwrap());
+        grammar.add_production(
+            Production::new("expr_tail", "+ term expr_tail | £".to_string()).unwrap(),
+        );
+        grammar.add_production(Production::new("term", "id".to_string()).unwrap());
+
+        grammar.compute_follows();
+
+        let expr_tail = grammar.get_production_by_var("expr_tail").unwrap();
+        let term = grammar.get_production_by_var("term").unwrap();
+
+        assert_eq!(expr_tail.firsts, hash_from_vec(vec!["+", EPSILON]));
+        assert_eq!(expr_tail.follows, hash_from_vec(vec![DOLLAR_SIGN]));
+        assert_eq!(term.follows, hash_from_vec(vec!["+", DOLLAR_SIGN]));
+    }
+
+    #[test]
+    fn test_follow_strict() {
+        let mut grammar = Grammar {
+            variables: vec!["S".to_string(), "A".to_string()],
+            terminals: vec!["a".to_string(), "b".to_string()],
+            productions: vec![],
+            initial_symbol: "S".to_string(),
+        };
+
+        grammar.add_production(Production::new("S", "A b".to_string()).unwrap());
+        grammar.add_production(Production::new("A", "a ? | £".to_string()).unwrap());
+
+        assert_eq!(
+            grammar.compute_follows_strict(),
+            Err(GrammarError::UnknownSymbol {
+                variable: "A".to_string(),
+                symbol: "?".to_string(),
+                offset: 2,
+            })
+        );
+
+        grammar.productions[1].derivation = "a | £".to_string();
+        assert!(grammar.compute_follows_strict().is_ok());
+        assert_eq!(grammar.productions[1].follows, hash_from_vec(vec!["b"]));
+    }
 }
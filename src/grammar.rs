@@ -1,16 +1,62 @@
 use super::production::Production;
+use crate::render::PlainText;
+use crate::token::{ordered_tokens, Token, TokenProcessor, EPSILON};
+use std::collections::HashSet;
 use std::fmt;
 
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub enum GrammarError {
-    InvalidVariable,
+    InvalidVariable(String),
+    UndeclaredVariable(String),
+    MissingInitialProduction(String),
+    // offset in bytes from the start of the derivation
+    UnknownSymbol {
+        variable: String,
+        symbol: String,
+        offset: usize,
+    },
 }
 
+impl fmt::Display for GrammarError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GrammarError::InvalidVariable(variable) => write!(
+                f,
+                "the variable '{}' must be a non-empty name without whitespace, '|' or '{}'.",
+                variable, EPSILON
+            ),
+            GrammarError::UndeclaredVariable(variable) => {
+                write!(
+                    f,
+                    "the variable '{}' has a production but is not declared.",
+                    variable
+                )
+            }
+            GrammarError::MissingInitialProduction(variable) => {
+                write!(f, "the initial symbol '{}' has no production.", variable)
+            }
+            GrammarError::UnknownSymbol {
+                variable,
+                symbol,
+                offset,
+            } => write!(
+                f,
+                "'{}' at offset {} in the derivation of '{}' is not a declared symbol.",
+                symbol, offset, variable
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GrammarError {}
+
 #[derive(Debug, Eq, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
 pub struct Grammar {
-    pub variables: Vec<char>,
+    pub variables: Vec<String>,
     pub terminals: Vec<String>,
     pub productions: Vec<Production>,
-    pub initial_symbol: char,
+    pub initial_symbol: String,
 }
 
 impl Grammar {
@@ -18,20 +64,22 @@ impl Grammar {
         self.initial_symbol.eq(&production.variable)
     }
 
-    pub fn is_variable(&self, ch: &char) -> bool {
-        self.variables.contains(ch)
+    pub fn is_variable(&self, name: &str) -> bool {
+        self.variables.iter().any(|v| v == name)
     }
 
-    pub fn is_terminal(&self, value: &String) -> bool {
-        self.terminals.contains(value)
+    pub fn is_terminal(&self, value: &str) -> bool {
+        self.terminals.iter().any(|t| t == value)
     }
 
-    pub fn add_variable(&mut self, variable: char) -> Result<(), GrammarError> {
-        if !variable.is_uppercase() {
-            return Err(GrammarError::InvalidVariable);
+    pub fn add_variable(&mut self, variable: String) -> Result<(), GrammarError> {
+        if !Production::is_valid_variable(&variable) {
+            return Err(GrammarError::InvalidVariable(variable));
         }
 
-        Ok(self.variables.push(variable))
+        self.variables.push(variable);
+
+        Ok(())
     }
 
     pub fn add_terminal(&mut self, terminal: String) {
@@ -44,35 +92,148 @@ impl Grammar {
         }
     }
 
-    pub fn get_production_by_var(&self, variable: char) -> Option<&Production> {
+    pub fn get_production_by_var(&self, variable: &str) -> Option<&Production> {
         self.productions.iter().find(|p| p.variable == variable)
     }
 
-    pub fn get_mut_production_by_var(&mut self, variable: char) -> Option<&mut Production> {
+    pub fn get_mut_production_by_var(&mut self, variable: &str) -> Option<&mut Production> {
         self.productions.iter_mut().find(|p| p.variable == variable)
     }
 
+    /// The tokens in the order every output uses, see `token::ordered_tokens`.
+    pub fn ordered_tokens(&self, tokens: &HashSet<Token>) -> Vec<Token> {
+        ordered_tokens(tokens, &self.terminals)
+    }
+
+    /// Every problem found in the declarations and productions, in production order.
+    pub fn validate(&self) -> Result<(), Vec<GrammarError>> {
+        let processor = TokenProcessor::new(self);
+        let mut errors: Vec<GrammarError> = self
+            .variables
+            .iter()
+            .filter(|variable| !Production::is_valid_variable(variable))
+            .map(|variable| GrammarError::InvalidVariable(variable.clone()))
+            .collect();
+
+        if self.get_production_by_var(&self.initial_symbol).is_none() {
+            errors.push(GrammarError::MissingInitialProduction(
+                self.initial_symbol.clone(),
+            ));
+        }
+
+        for production in self.productions.iter() {
+            let variable = &production.variable;
+
+            if !Production::is_valid_variable(variable) {
+                errors.push(GrammarError::InvalidVariable(variable.clone()));
+            } else if !self.is_variable(variable) {
+                let error = GrammarError::UndeclaredVariable(variable.clone());
+                if !errors.contains(&error) {
+                    errors.push(error);
+                }
+            }
+
+            for (offset, symbol) in processor.unknown_symbols(&production.derivation) {
+                errors.push(GrammarError::UnknownSymbol {
+                    variable: variable.clone(),
+                    symbol: symbol.to_string(),
+                    offset,
+                });
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
     pub fn productions_iter_mut(&mut self) -> std::slice::IterMut<'_, Production> {
         self.productions.iter_mut()
     }
 }
 
-impl fmt::Display for Grammar {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let divider: &str = &format!("*{:-<15}*{:-<15}*{:-<15}*{:-<15}*\n", "", "", "", "");
+// the sets of each production follow the terminal declarations of the grammar
+#[cfg(feature = "serde")]
+impl serde::Serialize for Grammar {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let productions: Vec<OrderedProduction<'_>> = self
+            .productions
+            .iter()
+            .map(|production| OrderedProduction {
+                variable: &production.variable,
+                derivation: &production.derivation,
+                firsts: self.ordered_tokens(&production.firsts),
+                follows: self.ordered_tokens(&production.follows),
+            })
+            .collect();
+
+        let mut state = serializer.serialize_struct("Grammar", 4)?;
+        state.serialize_field("variables", &self.variables)?;
+        state.serialize_field("terminals", &self.terminals)?;
+        state.serialize_field("productions", &productions)?;
+        state.serialize_field("initial_symbol", &self.initial_symbol)?;
+        state.end()
+    }
+}
 
-        f.write_str(divider)?;
-        writeln!(
-            f,
-            "|{:^15}|{:^15}|{:^15}|{:^15}|",
-            "Variable", "Derivation", "Firsts", "Follows"
-        )?;
-        f.write_str(divider)?;
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize)]
+struct OrderedProduction<'a> {
+    variable: &'a str,
+    derivation: &'a str,
+    firsts: Vec<Token>,
+    follows: Vec<Token>,
+}
 
-        for production in self.productions.iter() {
-            writeln!(f, "{}", production)?;
-        }
+impl fmt::Display for Grammar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render(&PlainText))
+    }
+}
 
-        f.write_str(divider)
+#[cfg(test)]
+mod test {
+    use crate::grammar::{Grammar, GrammarError};
+    use crate::production::Production;
+
+    #[test]
+    fn test_validate() {
+        let mut grammar = Grammar {
+            variables: vec!["E".to_string(), "T T".to_string()],
+            terminals: vec!["+".to_string(), "id".to_string()],
+            productions: vec![],
+            initial_symbol: "S".to_string(),
+        };
+
+        grammar.add_production(Production::new("E", "E + T | id".to_string()).unwrap());
+        grammar.add_production(Production::new("T", "id".to_string()).unwrap());
+
+        assert_eq!(
+            grammar.validate(),
+            Err(vec![
+                GrammarError::InvalidVariable("T T".to_string()),
+                GrammarError::MissingInitialProduction("S".to_string()),
+                GrammarError::UnknownSymbol {
+                    variable: "E".to_string(),
+                    symbol: "T".to_string(),
+                    offset: 4,
+                },
+                GrammarError::UndeclaredVariable("T".to_string()),
+            ])
+        );
+        assert_eq!(
+            Production::new("", "id".to_string())
+                .unwrap_err()
+                .to_string(),
+            "the variable '' must be a non-empty name without whitespace, '|' or '£'."
+        );
+
+        grammar.variables[1] = "T".to_string();
+        grammar.initial_symbol = "E".to_string();
+        assert_eq!(grammar.validate(), Ok(()));
     }
 }
@@ -0,0 +1,262 @@
+use crate::grammar::Grammar;
+use crate::rules::{fresh_variable, Rule};
+use crate::token::Token;
+use std::collections::HashSet;
+use std::fmt;
+
+/// Alternatives of a variable that shared a prefix, moved into a helper variable.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Factoring {
+    pub variable: String,
+    pub prefix: String,
+    pub helper: String,
+    pub alternatives: Vec<String>,
+}
+
+impl fmt::Display for Factoring {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let alternatives: Vec<String> = self
+            .alternatives
+            .iter()
+            .map(|a| format!("'{}'", a))
+            .collect();
+
+        write!(
+            f,
+            "'{}' factored out of {} in {} into {}",
+            self.prefix,
+            alternatives.join(", "),
+            self.variable,
+            self.helper
+        )
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct LeftFactoring {
+    pub grammar: Grammar,
+    pub factorings: Vec<Factoring>,
+}
+
+impl LeftFactoring {
+    /// The variable of the original grammar a variable stands for, through nested helpers.
+    pub fn origin<'a>(&'a self, variable: &'a str) -> &'a str {
+        match self.factorings.iter().find(|f| f.helper == variable) {
+            Some(factoring) => self.origin(&factoring.variable),
+            None => variable,
+        }
+    }
+}
+
+impl fmt::Display for LeftFactoring {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for factoring in self.factorings.iter() {
+            writeln!(f, "{}", factoring)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Grammar {
+    /// Replaces alternatives `A -> α β1 | α β2` sharing the longest prefix `α` with `A -> α A'`
+    /// and `A' -> β1 | β2`, until no two alternatives of a variable start with the same symbol.
+    /// Helpers are factored in turn, and repeated alternatives are dropped.
+    pub fn left_factor(&self) -> LeftFactoring {
+        let mut rules = self.rules();
+        let mut used: HashSet<String> = self
+            .variables
+            .iter()
+            .chain(self.terminals.iter())
+            .chain(rules.iter().map(|(variable, _)| variable))
+            .cloned()
+            .collect();
+        let mut factorings: Vec<Factoring> = vec![];
+
+        let mut i = 0;
+        while i < rules.len() {
+            let variable = rules[i].0.clone();
+            let mut helpers = 0;
+
+            let mut alternatives: Vec<Rule> = vec![];
+            for rule in rules[i].1.drain(..) {
+                if !alternatives.iter().any(|a| a.tokens == rule.tokens) {
+                    alternatives.push(rule);
+                }
+            }
+
+            while let Some(first) = shared_first_symbol(&alternatives) {
+                let (group, rest): (Vec<Rule>, Vec<Rule>) = alternatives
+                    .iter()
+                    .cloned()
+                    .partition(|rule| rule.tokens.first() == Some(&first));
+                let position = alternatives
+                    .iter()
+                    .position(|rule| rule.tokens.first() == Some(&first))
+                    .unwrap();
+
+                let mut length = 1;
+                while group.iter().all(|rule| {
+                    rule.tokens.len() > length && rule.tokens[length] == group[0].tokens[length]
+                }) {
+                    length += 1;
+                }
+
+                let helper = fresh_variable(&mut used, &variable);
+                let prefix: Vec<Token> = group[0].tokens[..length].to_vec();
+                let suffixes: Vec<Rule> = group
+                    .iter()
+                    .map(|rule| Rule::new(rule.tokens[length..].to_vec()))
+                    .collect();
+
+                factorings.push(Factoring {
+                    variable: variable.clone(),
+                    prefix: Rule::new(prefix.clone()).text(),
+                    helper: helper.clone(),
+                    alternatives: group.iter().map(|rule| rule.text()).collect(),
+                });
+
+                alternatives = rest;
+                alternatives.insert(
+                    position,
+                    Rule::new(
+                        prefix
+                            .into_iter()
+                            .chain([Token::Variable(helper.clone())])
+                            .collect(),
+                    ),
+                );
+                helpers += 1;
+                rules.insert(i + helpers, (helper, suffixes));
+            }
+
+            rules[i].1 = alternatives;
+            i += 1;
+        }
+
+        LeftFactoring {
+            grammar: self.with_rules(rules),
+            factorings,
+        }
+    }
+}
+
+// the first symbol, in alternative order, that starts more than one alternative
+fn shared_first_symbol(alternatives: &[Rule]) -> Option<Token> {
+    alternatives.iter().enumerate().find_map(|(i, rule)| {
+        let first = rule.tokens.first()?;
+        alternatives[i + 1..]
+            .iter()
+            .any(|other| other.tokens.first() == Some(first))
+            .then(|| first.clone())
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::grammar::Grammar;
+    use crate::production::Production;
+
+    #[test]
+    fn test_dangling_else() {
+        let mut grammar = Grammar {
+            variables: vec!["S".to_string(), "E".to_string()],
+            terminals: vec![
+                "if".to_string(),
+                "then".to_string(),
+                "else".to_string(),
+                "x".to_string(),
+                "b".to_string(),
+            ],
+            productions: vec![],
+            initial_symbol: "S".to_string(),
+        };
+
+        grammar.add_production(
+            Production::new("S", "if E then S | if E then S else S | x".to_string()).unwrap(),
+        );
+        grammar.add_production(Production::new("E", "b".to_string()).unwrap());
+
+        let result = grammar.left_factor();
+
+        let derivations: Vec<(&str, &str)> = result
+            .grammar
+            .productions
+            .iter()
+            .map(|p| (p.variable.as_str(), p.derivation.as_str()))
+            .collect();
+        assert_eq!(
+            derivations,
+            vec![
+                ("S", "if E then S S' | x"),
+                ("S'", "£ | else S"),
+                ("E", "b"),
+            ]
+        );
+        assert_eq!(result.grammar.variables, vec!["S", "S'", "E"]);
+        assert_eq!(
+            result.to_string(),
+            "'if E then S' factored out of 'if E then S', 'if E then S else S' in S into S'\n"
+        );
+    }
+
+    #[test]
+    fn test_nested_prefixes() {
+        let mut grammar = Grammar {
+            variables: vec!["A".to_string()],
+            terminals: vec![
+                "a".to_string(),
+                "b".to_string(),
+                "c".to_string(),
+                "d".to_string(),
+                "e".to_string(),
+                "f".to_string(),
+            ],
+            productions: vec![],
+            initial_symbol: "A".to_string(),
+        };
+
+        grammar.add_production(
+            Production::new("A", "a b c | f | a b d | a e | f".to_string()).unwrap(),
+        );
+        assert!(!grammar.ll1_conflicts().is_ll1());
+
+        let result = grammar.left_factor();
+
+        let derivations: Vec<(&str, &str)> = result
+            .grammar
+            .productions
+            .iter()
+            .map(|p| (p.variable.as_str(), p.derivation.as_str()))
+            .collect();
+        assert_eq!(
+            derivations,
+            vec![("A", "a A' | f"), ("A'", "b A'' | e"), ("A''", "c | d")]
+        );
+        assert_eq!(result.origin("A''"), "A");
+        assert_eq!(result.factorings[1].prefix, "b");
+
+        let mut factored = result.grammar;
+        factored.compute_follows();
+        assert!(factored.ll1_conflicts().is_ll1());
+    }
+
+    #[test]
+    fn test_grammar_without_common_prefixes_is_unchanged() {
+        let mut grammar = Grammar {
+            variables: vec!["E".to_string(), "Z".to_string(), "T".to_string()],
+            terminals: vec!["+".to_string(), "id".to_string()],
+            productions: vec![],
+            initial_symbol: "E".to_string(),
+        };
+
+        grammar.add_production(Production::new("E", "TZ".to_string()).unwrap());
+        grammar.add_production(Production::new("Z", "+TZ | £".to_string()).unwrap());
+        grammar.add_production(Production::new("T", "id".to_string()).unwrap());
+
+        let result = grammar.left_factor();
+
+        assert_eq!(result.grammar, grammar);
+        assert!(result.factorings.is_empty());
+    }
+}
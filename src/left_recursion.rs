@@ -0,0 +1,350 @@
+use crate::grammar::Grammar;
+use crate::rules::{fresh_variable, Rule};
+use crate::token::{Token, TokenProcessor};
+use std::collections::HashSet;
+
+/// A grammar without left recursion, with the tail variables introduced to remove it.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct LeftRecursionElimination {
+    pub grammar: Grammar,
+    // (tail, variable whose recursion it replaces)
+    pub tails: Vec<(String, String)>,
+    // variables whose alternatives were expanded to expose indirect recursion
+    pub substituted: Vec<String>,
+}
+
+impl LeftRecursionElimination {
+    /// The variable of the original grammar a variable stands for.
+    pub fn origin<'a>(&'a self, variable: &'a str) -> &'a str {
+        self.tails
+            .iter()
+            .find(|(tail, _)| tail == variable)
+            .map_or(variable, |(_, origin)| origin)
+    }
+}
+
+impl Grammar {
+    /// Variables that derive a sentential form starting with themselves, also through
+    /// other variables or nullable prefixes.
+    pub fn left_recursive_variables(&self) -> Vec<String> {
+        let nullable = self.nullable();
+        let processor = TokenProcessor::new(self);
+        let variables = self.rule_variables();
+
+        let leading = |variable: &str| -> Vec<String> {
+            let mut leading: Vec<String> = vec![];
+
+            for p in self.productions.iter().filter(|p| p.variable == variable) {
+                for slice in processor.process_derivation(&p.derivation) {
+                    for token in slice.tokens.iter() {
+                        match token {
+                            Token::Epsilon => continue,
+                            Token::Variable(name) => {
+                                leading.push(name.clone());
+                                if !nullable.contains(name) {
+                                    break;
+                                }
+                            }
+                            _ => break,
+                        }
+                    }
+                }
+            }
+
+            leading
+        };
+
+        variables
+            .iter()
+            .filter(|variable| reaches(&leading, variable, variable))
+            .cloned()
+            .collect()
+    }
+
+    /// Removes immediate and indirect left recursion with the standard ordering algorithm.
+    /// Variables are handled in declaration order, an alternative `Ai -> Aj γ` with `j < i` is
+    /// expanded with the alternatives of `Aj` when `Aj` leads back to `Ai`, then
+    /// `A -> A α | β` becomes `A -> β A'` and `A' -> α A' | £`. Alternatives `A -> A` are
+    /// dropped as they do not change the language.
+    ///
+    /// Left recursion hidden behind a nullable prefix, as in `A -> B A` with a nullable `B`,
+    /// is not removed, `left_recursive_variables` on the result still reports it.
+    pub fn eliminate_left_recursion(&self) -> LeftRecursionElimination {
+        let mut rules = self.rules();
+
+        let mut used: HashSet<String> = self
+            .variables
+            .iter()
+            .chain(self.terminals.iter())
+            .chain(rules.iter().map(|(variable, _)| variable))
+            .cloned()
+            .collect();
+        let mut tails: Vec<(String, String)> = vec![];
+        let mut substituted: Vec<String> = vec![];
+        let mut tail_rules: Vec<(usize, String, Vec<Rule>)> = vec![];
+
+        for i in 0..rules.len() {
+            let variable = rules[i].0.clone();
+
+            for j in 0..i {
+                let earlier = rules[j].0.clone();
+                let leads_back = {
+                    let leading = |v: &str| -> Vec<String> { leading_variables(&rules, v) };
+                    reaches(&leading, &earlier, &variable)
+                };
+                if !leads_back {
+                    continue;
+                }
+
+                let mut expanded: Vec<Rule> = vec![];
+                let mut changed = false;
+
+                for rule in rules[i].1.iter() {
+                    if rule.leading_variable() != Some(earlier.as_str()) {
+                        expanded.push(rule.clone());
+                        continue;
+                    }
+
+                    changed = true;
+                    for delta in rules[j].1.iter() {
+                        let tokens = delta
+                            .tokens
+                            .iter()
+                            .chain(rule.tokens[1..].iter())
+                            .cloned()
+                            .collect();
+                        expanded.push(Rule::new(tokens));
+                    }
+                }
+
+                if changed && !substituted.contains(&variable) {
+                    substituted.push(variable.clone());
+                }
+                rules[i].1 = expanded;
+            }
+
+            let (recursive, others): (Vec<Rule>, Vec<Rule>) = rules[i]
+                .1
+                .iter()
+                .filter(|rule| rule.tokens.len() != 1 || rule.leading_variable() != Some(&variable))
+                .cloned()
+                .partition(|rule| rule.leading_variable() == Some(variable.as_str()));
+
+            if recursive.is_empty() {
+                rules[i].1 = others;
+                continue;
+            }
+
+            let tail = fresh_variable(&mut used, &variable);
+
+            let reference = Token::Variable(tail.clone());
+            rules[i].1 = others
+                .into_iter()
+                .map(|beta| Rule::new(beta.tokens.into_iter().chain([reference.clone()]).collect()))
+                .collect();
+
+            let mut alphas: Vec<Rule> = recursive
+                .into_iter()
+                .map(|alpha| {
+                    Rule::new(
+                        alpha.tokens[1..]
+                            .iter()
+                            .cloned()
+                            .chain([reference.clone()])
+                            .collect(),
+                    )
+                })
+                .collect();
+            alphas.push(Rule::new(vec![]));
+
+            tails.push((tail.clone(), variable.clone()));
+            tail_rules.push((i, tail, alphas));
+        }
+
+        let mut ordered: Vec<(String, Vec<Rule>)> = vec![];
+        for (i, rule) in rules.into_iter().enumerate() {
+            ordered.push(rule);
+            ordered.extend(
+                tail_rules
+                    .iter()
+                    .filter(|(index, _, _)| *index == i)
+                    .map(|(_, tail, rules)| (tail.clone(), rules.clone())),
+            );
+        }
+        let grammar = self.with_rules(ordered);
+
+        LeftRecursionElimination {
+            grammar,
+            tails,
+            substituted,
+        }
+    }
+}
+
+fn leading_variables(rules: &[(String, Vec<Rule>)], variable: &str) -> Vec<String> {
+    rules
+        .iter()
+        .filter(|(v, _)| v == variable)
+        .flat_map(|(_, alternatives)| alternatives.iter())
+        .filter_map(|rule| rule.leading_variable().map(|v| v.to_string()))
+        .collect()
+}
+
+// whether target is reached from start through at least one leading edge
+fn reaches<F: Fn(&str) -> Vec<String>>(leading: &F, start: &str, target: &str) -> bool {
+    let mut visited: HashSet<String> = HashSet::new();
+    let mut stack: Vec<String> = leading(start);
+
+    while let Some(variable) = stack.pop() {
+        if variable == target {
+            return true;
+        }
+        if visited.insert(variable.clone()) {
+            stack.extend(leading(&variable));
+        }
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod test {
+    use crate::grammar::Grammar;
+    use crate::production::Production;
+    use crate::table::ParseTable;
+
+    #[test]
+    fn test_immediate_left_recursion() {
+        let mut grammar = Grammar {
+            variables: vec!["E".to_string(), "T".to_string(), "F".to_string()],
+            terminals: vec![
+                "+".to_string(),
+                "*".to_string(),
+                "(".to_string(),
+                ")".to_string(),
+                "id".to_string(),
+            ],
+            productions: vec![],
+            initial_symbol: "E".to_string(),
+        };
+
+        grammar.add_production(Production::new("E", "E + T | T".to_string()).unwrap());
+        grammar.add_production(Production::new("T", "T * F | F".to_string()).unwrap());
+        grammar.add_production(Production::new("F", "( E ) | id".to_string()).unwrap());
+
+        assert_eq!(grammar.left_recursive_variables(), vec!["E", "T"]);
+
+        let result = grammar.eliminate_left_recursion();
+
+        let derivations: Vec<(&str, &str)> = result
+            .grammar
+            .productions
+            .iter()
+            .map(|p| (p.variable.as_str(), p.derivation.as_str()))
+            .collect();
+        assert_eq!(
+            derivations,
+            vec![
+                ("E", "T E'"),
+                ("E'", "+ T E' | £"),
+                ("T", "F T'"),
+                ("T'", "* F T' | £"),
+                ("F", "( E ) | id"),
+            ]
+        );
+        assert_eq!(result.grammar.variables, vec!["E", "E'", "T", "T'", "F"]);
+        assert_eq!(result.origin("T'"), "T");
+        assert_eq!(result.origin("F"), "F");
+        assert!(result.substituted.is_empty());
+
+        let mut transformed = result.grammar;
+        assert!(transformed.left_recursive_variables().is_empty());
+        transformed.compute_follows();
+        assert!(ParseTable::new(&transformed).is_ok());
+    }
+
+    #[test]
+    fn test_indirect_left_recursion() {
+        let mut grammar = Grammar {
+            variables: vec!["S".to_string(), "A".to_string()],
+            terminals: vec![
+                "a".to_string(),
+                "b".to_string(),
+                "c".to_string(),
+                "d".to_string(),
+            ],
+            productions: vec![],
+            initial_symbol: "S".to_string(),
+        };
+
+        grammar.add_production(Production::new("S", "A a | b".to_string()).unwrap());
+        grammar.add_production(Production::new("A", "A c | S d | £".to_string()).unwrap());
+
+        assert_eq!(grammar.left_recursive_variables(), vec!["S", "A"]);
+
+        let result = grammar.eliminate_left_recursion();
+
+        let derivations: Vec<(&str, &str)> = result
+            .grammar
+            .productions
+            .iter()
+            .map(|p| (p.variable.as_str(), p.derivation.as_str()))
+            .collect();
+        assert_eq!(
+            derivations,
+            vec![
+                ("S", "A a | b"),
+                ("A", "b d A' | A'"),
+                ("A'", "c A' | a d A' | £"),
+            ]
+        );
+        assert_eq!(result.substituted, vec!["A"]);
+        assert_eq!(result.tails, vec![("A'".to_string(), "A".to_string())]);
+        assert!(result.grammar.left_recursive_variables().is_empty());
+    }
+
+    #[test]
+    fn test_fresh_tail_names() {
+        let mut grammar = Grammar {
+            variables: vec!["A".to_string(), "A'".to_string()],
+            terminals: vec!["a".to_string(), "b".to_string()],
+            productions: vec![],
+            initial_symbol: "A".to_string(),
+        };
+
+        grammar.add_production(Production::new("A", "A a | A' | A".to_string()).unwrap());
+        grammar.add_production(Production::new("A'", "b".to_string()).unwrap());
+
+        let result = grammar.eliminate_left_recursion();
+
+        let derivations: Vec<(&str, &str)> = result
+            .grammar
+            .productions
+            .iter()
+            .map(|p| (p.variable.as_str(), p.derivation.as_str()))
+            .collect();
+        assert_eq!(
+            derivations,
+            vec![("A", "A' A''"), ("A''", "a A'' | £"), ("A'", "b")]
+        );
+    }
+
+    #[test]
+    fn test_grammar_without_left_recursion_is_unchanged() {
+        let mut grammar = Grammar {
+            variables: vec!["E".to_string(), "Z".to_string(), "T".to_string()],
+            terminals: vec!["+".to_string(), "id".to_string()],
+            productions: vec![],
+            initial_symbol: "E".to_string(),
+        };
+
+        grammar.add_production(Production::new("E", "TZ".to_string()).unwrap());
+        grammar.add_production(Production::new("Z", "+TZ | £".to_string()).unwrap());
+        grammar.add_production(Production::new("T", "id".to_string()).unwrap());
+
+        let result = grammar.eliminate_left_recursion();
+
+        assert_eq!(result.grammar, grammar);
+        assert!(result.tails.is_empty());
+    }
+}
@@ -0,0 +1,26 @@
+pub mod analysis;
+pub mod antlr;
+pub mod bitset;
+pub mod bnf;
+pub mod conflict;
+pub mod ebnf;
+#[cfg(feature = "serde")]
+pub mod export;
+pub mod first;
+pub mod follow;
+pub mod grammar;
+pub mod left_factoring;
+pub mod left_recursion;
+pub mod load;
+pub mod nullable;
+pub mod parser;
+pub mod production;
+pub mod render;
+pub mod rules;
+pub mod symbol;
+pub mod table;
+pub mod token;
+pub mod trace;
+pub mod tree;
+pub mod useless;
+pub mod yacc;
@@ -0,0 +1,465 @@
+use crate::grammar::Grammar;
+use crate::production::Production;
+use crate::token::EPSILON;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum LoadError {
+    Io(String),
+    Syntax {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+}
+
+impl LoadError {
+    pub(crate) fn syntax(line: usize, column: usize, message: String) -> LoadError {
+        LoadError::Syntax {
+            line,
+            column,
+            message,
+        }
+    }
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Io(message) => f.write_str(message),
+            LoadError::Syntax {
+                line,
+                column,
+                message,
+            } => write!(f, "line {}, column {}: {}", line, column, message),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+/// The text of a grammar file, an unreadable file gives `LoadError::Io` naming the path.
+pub(crate) fn read_source(path: impl AsRef<Path>) -> Result<String, LoadError> {
+    let path = path.as_ref();
+
+    fs::read_to_string(path).map_err(|e| LoadError::Io(format!("{}: {}", path.display(), e)))
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(crate) enum LexemeKind {
+    Word(String),
+    Literal(String),
+    Directive(String),
+    Punct(&'static str),
+    Newline,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(crate) struct Lexeme {
+    pub kind: LexemeKind,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Lexeme {
+    pub fn describe(&self) -> String {
+        match &self.kind {
+            LexemeKind::Word(word) => format!("'{}'", word),
+            LexemeKind::Literal(text) => format!("'{}'", text),
+            LexemeKind::Directive(name) => format!("'%{}'", name),
+            LexemeKind::Punct(punct) => format!("'{}'", punct),
+            LexemeKind::Newline => "end of line".to_string(),
+        }
+    }
+
+    /// The lexeme as it could be written back in a grammar source.
+    pub fn text(&self) -> String {
+        match &self.kind {
+            LexemeKind::Word(word) => word.clone(),
+            LexemeKind::Literal(text) => format!("'{}'", text),
+            LexemeKind::Directive(name) => format!("%{}", name),
+            LexemeKind::Punct(punct) => punct.to_string(),
+            LexemeKind::Newline => String::new(),
+        }
+    }
+
+    pub fn error(&self, message: String) -> LoadError {
+        LoadError::syntax(self.line, self.column, message)
+    }
+}
+
+/// Splits a grammar source into lexemes. Words are runs of non-whitespace characters that stop
+/// before any of the punctuation, literals are quoted with `'` or `"`, `%name` is a directive
+/// and comments start with `#` or `//`, or are enclosed in `/* */`.
+pub(crate) fn lex(input: &str, punctuation: &[&'static str]) -> Result<Vec<Lexeme>, LoadError> {
+    let mut lexemes: Vec<Lexeme> = vec![];
+    let mut in_comment: Option<(usize, usize)> = None;
+
+    for (index, text) in input.lines().enumerate() {
+        let line = index + 1;
+        let chars: Vec<char> = text.chars().collect();
+        let mut i = 0;
+
+        while i < chars.len() {
+            let rest: String = chars[i..].iter().collect();
+            let column = i + 1;
+
+            if in_comment.is_some() {
+                match rest.find("*/") {
+                    Some(end) => {
+                        i += rest[..end].chars().count() + 2;
+                        in_comment = None;
+                    }
+                    None => i = chars.len(),
+                }
+                continue;
+            }
+
+            if chars[i].is_whitespace() {
+                i += 1;
+            } else if rest.starts_with('#') || rest.starts_with("//") {
+                i = chars.len();
+            } else if rest.starts_with("/*") {
+                in_comment = Some((line, column));
+                i += 2;
+            } else if let Some(punct) = longest_punct(&rest, punctuation) {
+                lexemes.push(Lexeme {
+                    kind: LexemeKind::Punct(punct),
+                    line,
+                    column,
+                });
+                i += punct.chars().count();
+            } else if chars[i] == '\'' || chars[i] == '"' {
+                let (literal, size) = literal(&chars[i..]).ok_or_else(|| {
+                    LoadError::syntax(line, column, "unterminated literal".to_string())
+                })?;
+
+                lexemes.push(Lexeme {
+                    kind: LexemeKind::Literal(literal),
+                    line,
+                    column,
+                });
+                i += size;
+            } else {
+                let start = i;
+                while i < chars.len() && !chars[i].is_whitespace() {
+                    let rest: String = chars[i..].iter().collect();
+                    if i > start && longest_punct(&rest, punctuation).is_some() {
+                        break;
+                    }
+                    i += 1;
+                }
+
+                let word: String = chars[start..i].iter().collect();
+                let kind = match word.strip_prefix('%') {
+                    Some(name) if !name.is_empty() => LexemeKind::Directive(name.to_string()),
+                    _ => LexemeKind::Word(word),
+                };
+
+                lexemes.push(Lexeme { kind, line, column });
+            }
+        }
+
+        lexemes.push(Lexeme {
+            kind: LexemeKind::Newline,
+            line,
+            column: chars.len() + 1,
+        });
+    }
+
+    if let Some((line, column)) = in_comment {
+        return Err(LoadError::syntax(
+            line,
+            column,
+            "unterminated comment".to_string(),
+        ));
+    }
+
+    Ok(lexemes)
+}
+
+fn longest_punct(input: &str, punctuation: &[&'static str]) -> Option<&'static str> {
+    punctuation
+        .iter()
+        .filter(|p| input.starts_with(*p))
+        .max_by_key(|p| p.len())
+        .copied()
+}
+
+// the quoted text and the number of characters read, including both quotes
+fn literal(chars: &[char]) -> Option<(String, usize)> {
+    let quote = chars[0];
+    let mut text = String::new();
+    let mut i = 1;
+
+    while i < chars.len() {
+        match chars[i] {
+            '\\' if i + 1 < chars.len() => {
+                // only quotes and backslashes are unescaped, `'\n'` stays a two character name
+                if chars[i + 1] != quote && chars[i + 1] != '\\' {
+                    text.push('\\');
+                }
+                text.push(chars[i + 1]);
+                i += 2;
+            }
+            ch if ch == quote => return Some((text, i + 1)),
+            ch => {
+                text.push(ch);
+                i += 1;
+            }
+        }
+    }
+
+    None
+}
+
+// spaces in place of the characters, so code can be removed from a source without moving
+// the lexemes that follow it
+pub(crate) fn blank(output: &mut String, chars: &[char]) {
+    for ch in chars {
+        output.push(if *ch == '\n' { '\n' } else { ' ' });
+    }
+}
+
+pub(crate) fn find(chars: &[char], from: usize, text: &str) -> Option<usize> {
+    let text: Vec<char> = text.chars().collect();
+
+    (from..chars.len()).find(|i| chars[*i..].starts_with(&text))
+}
+
+// index after the closing quote, or the end of the line for an unterminated literal
+pub(crate) fn quoted_end(chars: &[char], start: usize) -> usize {
+    let mut i = start + 1;
+
+    while i < chars.len() && chars[i] != '\n' {
+        match chars[i] {
+            '\\' => i += 2,
+            ch if ch == chars[start] => return i + 1,
+            _ => i += 1,
+        }
+    }
+
+    i.min(chars.len())
+}
+
+// index after the brace closing the block, skipping braces inside literals and comments
+pub(crate) fn block_end(chars: &[char], start: usize) -> Option<usize> {
+    let mut depth = 0;
+    let mut i = start;
+
+    while i < chars.len() {
+        match chars[i] {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i + 1);
+                }
+            }
+            '\'' | '"' => {
+                i = quoted_end(chars, i);
+                continue;
+            }
+            '/' if chars.get(i + 1) == Some(&'*') => {
+                i = find(chars, i + 2, "*/")? + 2;
+                continue;
+            }
+            '/' if chars.get(i + 1) == Some(&'/') => {
+                i = find(chars, i, "\n").unwrap_or(chars.len());
+                continue;
+            }
+            _ => {}
+        }
+
+        i += 1;
+    }
+
+    None
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(crate) enum Item {
+    Symbol(String),
+    Literal(String),
+    Epsilon,
+}
+
+pub(crate) type RuleAlternative = Vec<(Item, Lexeme)>;
+
+/// A quoted literal that cannot be a terminal name as written, `|` separates alternatives in
+/// a derivation and `£` is epsilon, so they become `¦` and `₤`, and whitespace `␣`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RenamedLiteral {
+    pub terminal: String,
+    pub text: String,
+}
+
+pub(crate) fn source_text<'a>(literals: &'a [RenamedLiteral], terminal: &'a str) -> &'a str {
+    literals
+        .iter()
+        .find(|l| l.terminal == terminal)
+        .map_or(terminal, |l| l.text.as_str())
+}
+
+/// Rules read by a front end, before the variables and terminals are known.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub(crate) struct RuleSet {
+    pub variables: Vec<String>,
+    pub terminals: Vec<String>,
+    pub start: Option<String>,
+    pub rules: Vec<(String, Vec<RuleAlternative>)>,
+    pub literals: Vec<RenamedLiteral>,
+}
+
+impl RuleSet {
+    /// The terminal name of a quoted literal, renamed when the text cannot be one.
+    pub fn literal(&mut self, text: &str) -> String {
+        if is_valid_terminal(text) {
+            return text.to_string();
+        }
+        if let Some(literal) = self.literals.iter().find(|l| l.text == text) {
+            return literal.terminal.clone();
+        }
+
+        let mut terminal: String = text
+            .chars()
+            .map(|ch| match ch {
+                '|' => '¦',
+                ch if EPSILON.starts_with(ch) => '₤',
+                ch if ch.is_whitespace() => '␣',
+                ch => ch,
+            })
+            .collect();
+        while terminal.is_empty()
+            || self.variables.contains(&terminal)
+            || self.terminals.contains(&terminal)
+            || self.literals.iter().any(|l| l.terminal == terminal)
+        {
+            terminal.push('\'');
+        }
+
+        self.literals.push(RenamedLiteral {
+            terminal: terminal.clone(),
+            text: text.to_string(),
+        });
+
+        terminal
+    }
+
+    pub fn declare_variable(&mut self, lexeme: &Lexeme, name: &str) -> Result<(), LoadError> {
+        if self.terminals.iter().any(|t| t == name) {
+            return Err(lexeme.error(format!("'{}' is already declared as a terminal", name)));
+        }
+        if !Production::is_valid_variable(name) {
+            return Err(lexeme.error(format!("'{}' is not a valid variable name", name)));
+        }
+
+        if !self.variables.iter().any(|v| v == name) {
+            self.variables.push(name.to_string());
+        }
+
+        Ok(())
+    }
+
+    pub fn declare_terminal(&mut self, lexeme: &Lexeme, name: &str) -> Result<(), LoadError> {
+        if self.variables.iter().any(|v| v == name) || self.has_rule(name) {
+            return Err(lexeme.error(format!("'{}' is already used as a variable", name)));
+        }
+        if !is_valid_terminal(name) {
+            return Err(lexeme.error(format!("'{}' is not a valid terminal name", name)));
+        }
+
+        if !self.terminals.iter().any(|t| t == name) {
+            self.terminals.push(name.to_string());
+        }
+
+        Ok(())
+    }
+
+    pub fn has_rule(&self, variable: &str) -> bool {
+        self.rules.iter().any(|(v, _)| v == variable)
+    }
+
+    /// Appends alternatives to the rule of the variable, so a variable written in several
+    /// rules ends up with a single production.
+    pub fn add_rule(
+        &mut self,
+        lexeme: &Lexeme,
+        variable: &str,
+        alternatives: Vec<RuleAlternative>,
+    ) -> Result<(), LoadError> {
+        self.declare_variable(lexeme, variable)?;
+
+        match self.rules.iter_mut().find(|(v, _)| v == variable) {
+            Some((_, existing)) => existing.extend(alternatives),
+            None => self.rules.push((variable.to_string(), alternatives)),
+        }
+
+        Ok(())
+    }
+
+    /// Builds the grammar, every symbol that is not a variable is taken as a terminal and the
+    /// start symbol defaults to the variable of the first rule.
+    pub fn into_grammar(self, end: &Lexeme) -> Result<(Grammar, Vec<RenamedLiteral>), LoadError> {
+        let initial_symbol = match (&self.start, self.rules.first()) {
+            (Some(start), _) => start.clone(),
+            (None, Some((variable, _))) => variable.clone(),
+            (None, None) => return Err(end.error("the grammar has no rules".to_string())),
+        };
+
+        let mut grammar = Grammar {
+            variables: self.variables.clone(),
+            terminals: self.terminals.clone(),
+            productions: vec![],
+            initial_symbol,
+        };
+
+        for (variable, alternatives) in self.rules.iter() {
+            let mut derivations: Vec<String> = vec![];
+
+            for alternative in alternatives.iter() {
+                let mut symbols: Vec<&str> = vec![];
+
+                for (item, lexeme) in alternative.iter() {
+                    match item {
+                        Item::Epsilon => symbols.push(EPSILON),
+                        Item::Symbol(name) if grammar.is_variable(name) => symbols.push(name),
+                        Item::Symbol(name) | Item::Literal(name) => {
+                            if grammar.is_variable(name) {
+                                return Err(lexeme.error(format!(
+                                    "the literal '{}' clashes with a variable",
+                                    name
+                                )));
+                            }
+                            if !is_valid_terminal(name) {
+                                return Err(lexeme
+                                    .error(format!("'{}' is not a valid terminal name", name)));
+                            }
+                            if !grammar.is_terminal(name) {
+                                grammar.add_terminal(name.clone());
+                            }
+                            symbols.push(name);
+                        }
+                    }
+                }
+
+                if symbols.is_empty() {
+                    symbols.push(EPSILON);
+                }
+                derivations.push(symbols.join(" "));
+            }
+
+            let production = Production::new(variable, derivations.join(" | "))
+                .map_err(|e| end.error(e.to_string()))?;
+            grammar.add_production(production);
+        }
+
+        Ok((grammar, self.literals))
+    }
+}
+
+pub(crate) fn is_valid_terminal(name: &str) -> bool {
+    Production::is_valid_variable(name)
+}
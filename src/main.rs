@@ -1,11 +1,7 @@
-mod first;
-mod follow;
-mod grammar;
-mod production;
-mod token;
-
-use grammar::Grammar;
-use production::Production;
+use first_follow::bnf;
+use first_follow::grammar::Grammar;
+use first_follow::production::Production;
+use first_follow::render;
 // use std::io;
 // use std::str::FromStr;
 
@@ -110,8 +106,37 @@ use production::Production;
 // }
 
 fn main() {
+    if let Some(path) = std::env::args().nth(1) {
+        match bnf::read(&path) {
+            Ok(mut grammar) => {
+                let format = std::env::args()
+                    .nth(2)
+                    .unwrap_or_else(|| "plain".to_string());
+                let renderer = render::renderer(&format).unwrap_or_else(|| {
+                    eprintln!("unknown format '{}'", format);
+                    std::process::exit(1);
+                });
+
+                eprint!("{}", grammar.useless_symbols());
+                grammar.compute_follows();
+                print!("{}", grammar.render(renderer.as_ref()));
+            }
+            Err(e) => {
+                eprintln!("{}: {}", path, e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
     let mut grammar = Grammar {
-        variables: vec!['E', 'Z', 'T', 'Y', 'X'],
+        variables: vec![
+            "E".to_string(),
+            "E'".to_string(),
+            "T".to_string(),
+            "T'".to_string(),
+            "F".to_string(),
+        ],
         terminals: vec![
             "+".to_string(),
             "*".to_string(),
@@ -120,14 +145,14 @@ fn main() {
             ")".to_string(),
         ],
         productions: vec![],
-        initial_symbol: 'S',
+        initial_symbol: "E".to_string(),
     };
 
-    grammar.add_production(Production::new('E', "TZ".to_string()).unwrap());
-    grammar.add_production(Production::new('Z', "+TZ | £".to_string()).unwrap());
-    grammar.add_production(Production::new('T', "XY".to_string()).unwrap());
-    grammar.add_production(Production::new('Y', "*XY | £".to_string()).unwrap());
-    grammar.add_production(Production::new('X', "(E) | id".to_string()).unwrap());
+    grammar.add_production(Production::new("E", "T E'".to_string()).unwrap());
+    grammar.add_production(Production::new("E'", "+ T E' | £".to_string()).unwrap());
+    grammar.add_production(Production::new("T", "F T'".to_string()).unwrap());
+    grammar.add_production(Production::new("T'", "* F T' | £".to_string()).unwrap());
+    grammar.add_production(Production::new("F", "( E ) | id".to_string()).unwrap());
 
     grammar.compute_firsts();
 
@@ -0,0 +1,133 @@
+use crate::bitset::BitSet;
+use crate::grammar::Grammar;
+use crate::symbol::{Symbol, SymbolTable};
+use crate::token::{Token, TokenProcessor};
+use std::collections::HashSet;
+
+/// Whether every token of the sequence can derive epsilon, an empty sequence is nullable.
+pub fn sequence_is_nullable(tokens: &[Token], nullable: &HashSet<String>) -> bool {
+    tokens.iter().all(|token| match token {
+        Token::Epsilon => true,
+        Token::Variable(name) => nullable.contains(name),
+        _ => false,
+    })
+}
+
+pub(crate) fn symbols_are_nullable(symbols: &[Symbol], nullable: &BitSet) -> bool {
+    symbols.iter().all(|symbol| match symbol {
+        Symbol::Epsilon => true,
+        Symbol::Variable(id) => nullable.contains(*id),
+        Symbol::Terminal(_) => false,
+    })
+}
+
+/// Ids of the variables that derive epsilon, repeating until no new variable is found.
+pub(crate) fn nullable_variables(
+    table: &SymbolTable,
+    alternatives: &[(usize, Vec<Symbol>)],
+) -> BitSet {
+    let mut nullable = table.variable_set();
+    let mut changed = true;
+
+    while changed {
+        changed = false;
+
+        for (variable, symbols) in alternatives.iter() {
+            if !nullable.contains(*variable) && symbols_are_nullable(symbols, &nullable) {
+                nullable.insert(*variable);
+                changed = true;
+            }
+        }
+    }
+
+    nullable
+}
+
+impl Grammar {
+    /// Computes the set of variables that derive epsilon.
+    pub fn nullable(&self) -> HashSet<String> {
+        let table = SymbolTable::new(self);
+        let nullable = nullable_variables(&table, &table.alternatives(self));
+
+        nullable
+            .iter()
+            .map(|id| table.variable_name(id).to_string())
+            .collect()
+    }
+
+    /// Tells for each alternative of the variable, in derivation order, whether it derives epsilon.
+    pub fn nullable_alternatives(&self, variable: &str) -> Vec<bool> {
+        let nullable = self.nullable();
+        let processor = TokenProcessor::new(self);
+
+        self.productions
+            .iter()
+            .filter(|p| p.variable == variable)
+            .flat_map(|p| processor.known_alternatives(&p.derivation))
+            .map(|slice| slice.is_some_and(|s| sequence_is_nullable(&s.tokens, &nullable)))
+            .collect()
+    }
+
+    pub fn is_nullable(&self, variable: &str) -> bool {
+        self.nullable().contains(variable)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::grammar::Grammar;
+    use crate::production::Production;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_nullable() {
+        let mut grammar = Grammar {
+            variables: vec![
+                "S".to_string(),
+                "A".to_string(),
+                "B".to_string(),
+                "C".to_string(),
+            ],
+            terminals: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            productions: vec![],
+            initial_symbol: "S".to_string(),
+        };
+
+        grammar.add_production(Production::new("S", "AB | c".to_string()).unwrap());
+        grammar.add_production(Production::new("A", "aA | B".to_string()).unwrap());
+        grammar.add_production(Production::new("B", "bB | £".to_string()).unwrap());
+        grammar.add_production(Production::new("C", "Cc | AcB".to_string()).unwrap());
+
+        let expected: HashSet<String> = vec!["S".to_string(), "A".to_string(), "B".to_string()]
+            .into_iter()
+            .collect();
+
+        assert_eq!(grammar.nullable(), expected);
+        assert!(!grammar.is_nullable("C"), "Testing variable C");
+    }
+
+    #[test]
+    fn test_nullable_alternatives() {
+        let mut grammar = Grammar {
+            variables: vec![
+                "S".to_string(),
+                "A".to_string(),
+                "B".to_string(),
+                "C".to_string(),
+            ],
+            terminals: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            productions: vec![],
+            initial_symbol: "S".to_string(),
+        };
+
+        grammar.add_production(Production::new("S", "AB | c".to_string()).unwrap());
+        grammar.add_production(Production::new("A", "aA | B".to_string()).unwrap());
+        grammar.add_production(Production::new("B", "bB | £".to_string()).unwrap());
+        grammar.add_production(Production::new("C", "Cc | AcB".to_string()).unwrap());
+
+        assert_eq!(grammar.nullable_alternatives("S"), vec![true, false]);
+        assert_eq!(grammar.nullable_alternatives("A"), vec![false, true]);
+        assert_eq!(grammar.nullable_alternatives("B"), vec![false, true]);
+        assert_eq!(grammar.nullable_alternatives("C"), vec![false, false]);
+    }
+}
@@ -0,0 +1,1017 @@
+use crate::grammar::Grammar;
+use crate::table::{Alternative, ErrorHandler, ParseTable};
+use crate::token::Token;
+use crate::trace::{Action, ParseTrace, TraceStep};
+use crate::tree::ParseTree;
+use std::fmt;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ParseError {
+    MissingDollarSign,
+    InvalidToken {
+        position: usize,
+        token: Token,
+    },
+    UnexpectedToken {
+        position: usize,
+        found: Token,
+        expected: Vec<Token>,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::MissingDollarSign => {
+                write!(
+                    f,
+                    "the input must end with a single '{}'",
+                    Token::DollarSign
+                )
+            }
+            ParseError::InvalidToken { position, token } => write!(
+                f,
+                "'{}' at position {} is not a terminal of the grammar",
+                token, position
+            ),
+            ParseError::UnexpectedToken {
+                position,
+                found,
+                expected,
+            } => {
+                let expected: Vec<String> = expected.iter().map(|t| t.to_string()).collect();
+                write!(
+                    f,
+                    "unexpected '{}' at position {}, expected one of: {}",
+                    found,
+                    position,
+                    expected.join(",")
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Minimal change to the input suggested by a phrase-level error handler,
+/// positions refer to the input given to the parser.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Repair {
+    Insert { position: usize, token: Token },
+    Delete { position: usize, token: Token },
+}
+
+impl fmt::Display for Repair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Repair::Insert { position, token } => {
+                write!(f, "insert '{}' at position {}", token, position)
+            }
+            Repair::Delete { position, token } => {
+                write!(f, "delete '{}' at position {}", token, position)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SyntaxError {
+    pub error: ParseError,
+    pub repair: Option<Repair>,
+}
+
+impl SyntaxError {
+    pub fn new(error: ParseError, repair: Option<Repair>) -> SyntaxError {
+        SyntaxError { error, repair }
+    }
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.repair {
+            Some(repair) => write!(f, "{}, suggestion: {}", self.error, repair),
+            None => write!(f, "{}", self.error),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct PredictiveParser<'a> {
+    grammar: &'a Grammar,
+    table: &'a ParseTable,
+}
+
+impl<'a> PredictiveParser<'a> {
+    pub fn new(grammar: &'a Grammar, table: &'a ParseTable) -> PredictiveParser<'a> {
+        PredictiveParser { grammar, table }
+    }
+
+    pub fn accepts(&self, input: &[Token]) -> bool {
+        self.parse(input).is_ok()
+    }
+
+    /// Runs the stack algorithm over terminals terminated by a `Token::DollarSign`
+    /// and builds the concrete syntax tree of the accepted input.
+    pub fn parse(&self, input: &[Token]) -> Result<ParseTree, ParseError> {
+        match self.run(input, None, false) {
+            Ok(derivation) => Ok(self.build_tree(input, derivation)),
+            Err(mut errors) => Err(errors.remove(0).error),
+        }
+    }
+
+    /// Parses the whole input collecting every error. Empty cells with an error handler in the
+    /// table are repaired as the handler says and the repair is reported as a suggestion, other
+    /// errors are recovered in panic mode: the parser skips input until a token that
+    /// synchronises the variable on top of the stack.
+    pub fn parse_with_recovery(&self, input: &[Token]) -> Result<ParseTree, Vec<SyntaxError>> {
+        self.run(input, None, true)
+            .map(|derivation| self.build_tree(input, derivation))
+    }
+
+    /// Parses the input recording the stack, the remaining input and the action of every step.
+    pub fn trace(&self, input: &[Token]) -> ParseTrace {
+        let mut trace = ParseTrace::default();
+        // the outcome is already recorded as the last step
+        let _ = self.run(input, Some(&mut trace), false);
+
+        trace
+    }
+
+    pub fn trace_with_recovery(&self, input: &[Token]) -> ParseTrace {
+        let mut trace = ParseTrace::default();
+        let _ = self.run(input, Some(&mut trace), true);
+
+        trace
+    }
+
+    fn build_tree(&self, input: &[Token], derivation: Vec<Alternative>) -> ParseTree {
+        let mut alternatives = derivation.into_iter();
+        let mut leaves = input.iter().cloned();
+
+        ParseTree::from_derivation(&self.grammar.initial_symbol, &mut alternatives, &mut leaves)
+            .expect("an accepted input is a leftmost derivation of the initial symbol")
+    }
+
+    fn run(
+        &self,
+        input: &[Token],
+        mut trace: Option<&mut ParseTrace>,
+        recover: bool,
+    ) -> Result<Vec<Alternative>, Vec<SyntaxError>> {
+        let mut derivation: Vec<Alternative> = vec![];
+        let mut errors: Vec<SyntaxError> = vec![];
+        let mut stack: Vec<Token> = vec![
+            Token::DollarSign,
+            Token::Variable(self.grammar.initial_symbol.clone()),
+        ];
+        // repairs edit a copy of the input, origins map it back to the given positions
+        let mut tokens: Vec<Token> = input.to_vec();
+        let mut origins: Vec<usize> = (0..input.len()).collect();
+        let mut position = 0;
+        // set while skipping input, so one error is not reported once per skipped token
+        let mut panicking = false;
+        // a single repair per input position, so handlers cannot undo each other forever
+        let mut repaired_at: Option<usize> = None;
+
+        let mut record = |stack: &[Token], remaining: &[Token], action: Action| {
+            if let Some(trace) = trace.as_mut() {
+                trace.steps.push(TraceStep::new(stack, remaining, action));
+            }
+        };
+
+        if input.last() != Some(&Token::DollarSign) {
+            record(&stack, input, Action::Error(ParseError::MissingDollarSign));
+            return Err(vec![SyntaxError::new(ParseError::MissingDollarSign, None)]);
+        }
+
+        while let Some(top) = stack.last().cloned() {
+            let lookahead = tokens[position].clone();
+            let origin = origins[position];
+
+            if !self.is_valid_lookahead(&tokens, position) {
+                let error = ParseError::InvalidToken {
+                    position: origin,
+                    token: lookahead.clone(),
+                };
+                record(&stack, &tokens[position..], Action::Error(error.clone()));
+                errors.push(SyntaxError::new(error, None));
+
+                if !recover {
+                    return Err(errors);
+                }
+
+                record(&stack, &tokens[position..], Action::Skip(lookahead));
+                position += 1;
+                continue;
+            }
+
+            let action = match &top {
+                Token::Variable(name) => match self.table.get(name, &lookahead) {
+                    Some(alternative) => Action::Expand(alternative.clone()),
+                    None => Action::Error(ParseError::UnexpectedToken {
+                        position: origin,
+                        found: lookahead.clone(),
+                        expected: self.expected_for(&top),
+                    }),
+                },
+                Token::DollarSign if lookahead == Token::DollarSign => Action::Accept,
+                _ if top == lookahead => Action::Match(top.clone()),
+                _ => Action::Error(ParseError::UnexpectedToken {
+                    position: origin,
+                    found: lookahead.clone(),
+                    expected: self.expected_for(&top),
+                }),
+            };
+
+            match action {
+                Action::Error(error) => {
+                    if !recover {
+                        record(&stack, &tokens[position..], Action::Error(error.clone()));
+                        errors.push(SyntaxError::new(error, None));
+                        return Err(errors);
+                    }
+
+                    let repair = match repaired_at {
+                        Some(at) if at == origin => None,
+                        _ => self.repair_for(&top, &lookahead, origin),
+                    };
+
+                    if let Some(repair) = repair {
+                        record(&stack, &tokens[position..], Action::Error(error.clone()));
+                        record(&stack, &tokens[position..], Action::Repair(repair.clone()));
+
+                        match &repair {
+                            Repair::Insert { token, .. } => {
+                                tokens.insert(position, token.clone());
+                                origins.insert(position, origin);
+                            }
+                            Repair::Delete { .. } => {
+                                tokens.remove(position);
+                                origins.remove(position);
+                            }
+                        }
+
+                        errors.push(SyntaxError::new(error, Some(repair)));
+                        repaired_at = Some(origin);
+                        panicking = false;
+                        continue;
+                    }
+
+                    if !panicking {
+                        record(&stack, &tokens[position..], Action::Error(error.clone()));
+                        errors.push(SyntaxError::new(error, None));
+                    }
+
+                    panicking = true;
+                    let recovery = self.recovery_for(&stack, &lookahead);
+                    record(&stack, &tokens[position..], recovery.clone());
+
+                    match recovery {
+                        Action::Skip(_) => position += 1,
+                        _ => {
+                            stack.pop();
+                        }
+                    }
+                }
+                Action::Expand(alternative) => {
+                    panicking = false;
+                    record(
+                        &stack,
+                        &tokens[position..],
+                        Action::Expand(alternative.clone()),
+                    );
+                    stack.pop();
+
+                    for token in alternative.tokens.iter().rev() {
+                        if *token != Token::Epsilon {
+                            stack.push(token.clone());
+                        }
+                    }
+                    derivation.push(alternative);
+                }
+                Action::Match(_) => {
+                    panicking = false;
+                    record(&stack, &tokens[position..], action);
+                    stack.pop();
+                    position += 1;
+                }
+                _ => {
+                    record(&stack, &tokens[position..], action);
+                    break;
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(derivation)
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn repair_for(&self, top: &Token, lookahead: &Token, position: usize) -> Option<Repair> {
+        let variable = match top {
+            Token::Variable(name) => name,
+            _ => return None,
+        };
+
+        match self.table.error_handler(variable, lookahead)? {
+            ErrorHandler::Insert(token) => Some(Repair::Insert {
+                position,
+                token: token.clone(),
+            }),
+            // the end marker is never deleted
+            ErrorHandler::Delete if *lookahead != Token::DollarSign => Some(Repair::Delete {
+                position,
+                token: lookahead.clone(),
+            }),
+            ErrorHandler::Delete => None,
+        }
+    }
+
+    // a variable is popped when the lookahead is in its follows, which is where the input
+    // around it can continue, otherwise the lookahead is discarded. The end marker is never
+    // skipped and the initial symbol is kept while there is input left to resynchronise on.
+    fn recovery_for(&self, stack: &[Token], lookahead: &Token) -> Action {
+        let top = stack[stack.len() - 1].clone();
+
+        match top {
+            Token::Variable(ref name) => {
+                let only_symbol = stack.len() == 2;
+                let synchronises = self.grammar.follows_of(name).contains(lookahead);
+
+                if *lookahead == Token::DollarSign || (synchronises && !only_symbol) {
+                    Action::Pop(top)
+                } else {
+                    Action::Skip(lookahead.clone())
+                }
+            }
+            Token::DollarSign => Action::Skip(lookahead.clone()),
+            _ => Action::Pop(top),
+        }
+    }
+
+    fn is_valid_lookahead(&self, input: &[Token], position: usize) -> bool {
+        match &input[position] {
+            Token::Terminal(s) => self.grammar.is_terminal(s),
+            Token::DollarSign => position + 1 == input.len(),
+            _ => false,
+        }
+    }
+
+    fn expected_for(&self, top: &Token) -> Vec<Token> {
+        match top {
+            Token::Variable(name) => self
+                .table
+                .row(name)
+                .into_iter()
+                .map(|(lookahead, _)| lookahead.clone())
+                .collect(),
+            _ => vec![top.clone()],
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::grammar::Grammar;
+    use crate::parser::{ParseError, PredictiveParser, Repair};
+    use crate::production::Production;
+    use crate::table::{ErrorHandler, ParseTable, TableError};
+    use crate::token::Token;
+
+    fn input(terminals: Vec<&str>) -> Vec<Token> {
+        let mut tokens: Vec<Token> = terminals
+            .iter()
+            .map(|t| Token::Terminal(t.to_string()))
+            .collect();
+        tokens.push(Token::DollarSign);
+        tokens
+    }
+
+    #[test]
+    fn test_accepts() {
+        let mut grammar = Grammar {
+            variables: vec![
+                "E".to_string(),
+                "Z".to_string(),
+                "T".to_string(),
+                "Y".to_string(),
+                "F".to_string(),
+            ],
+            terminals: vec![
+                "+".to_string(),
+                "*".to_string(),
+                "(".to_string(),
+                "id".to_string(),
+                ")".to_string(),
+            ],
+            productions: vec![],
+            initial_symbol: "E".to_string(),
+        };
+
+        grammar.add_production(Production::new("E", "TZ".to_string()).unwrap());
+        grammar.add_production(Production::new("Z", "+TZ | £".to_string()).unwrap());
+        grammar.add_production(Production::new("T", "FY".to_string()).unwrap());
+        grammar.add_production(Production::new("Y", "*FY | £".to_string()).unwrap());
+        grammar.add_production(Production::new("F", "(E) | id".to_string()).unwrap());
+
+        grammar.compute_follows();
+
+        let table = ParseTable::new(&grammar).unwrap();
+        let parser = PredictiveParser::new(&grammar, &table);
+
+        assert!(parser.accepts(&input(vec!["id"])));
+        assert!(parser.accepts(&input(vec!["id", "+", "id", "*", "id"])));
+        assert!(parser.accepts(&input(vec!["(", "id", "+", "id", ")", "*", "id"])));
+    }
+
+    #[test]
+    fn test_rejects() {
+        let mut grammar = Grammar {
+            variables: vec![
+                "E".to_string(),
+                "Z".to_string(),
+                "T".to_string(),
+                "Y".to_string(),
+                "F".to_string(),
+            ],
+            terminals: vec![
+                "+".to_string(),
+                "*".to_string(),
+                "(".to_string(),
+                "id".to_string(),
+                ")".to_string(),
+            ],
+            productions: vec![],
+            initial_symbol: "E".to_string(),
+        };
+
+        grammar.add_production(Production::new("E", "TZ".to_string()).unwrap());
+        grammar.add_production(Production::new("Z", "+TZ | £".to_string()).unwrap());
+        grammar.add_production(Production::new("T", "FY".to_string()).unwrap());
+        grammar.add_production(Production::new("Y", "*FY | £".to_string()).unwrap());
+        grammar.add_production(Production::new("F", "(E) | id".to_string()).unwrap());
+
+        grammar.compute_follows();
+
+        let table = ParseTable::new(&grammar).unwrap();
+        let parser = PredictiveParser::new(&grammar, &table);
+
+        assert!(!parser.accepts(&input(vec![])));
+        assert!(!parser.accepts(&input(vec!["id", "+"])));
+        assert!(!parser.accepts(&input(vec!["(", "id"])));
+        assert_eq!(
+            parser.parse(&input(vec!["id", "id"])),
+            Err(ParseError::UnexpectedToken {
+                position: 1,
+                found: Token::Terminal("id".to_string()),
+                expected: input(vec!["+", "*", ")"]),
+            })
+        );
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let mut grammar = Grammar {
+            variables: vec![
+                "E".to_string(),
+                "Z".to_string(),
+                "T".to_string(),
+                "Y".to_string(),
+                "F".to_string(),
+            ],
+            terminals: vec![
+                "+".to_string(),
+                "*".to_string(),
+                "(".to_string(),
+                "id".to_string(),
+                ")".to_string(),
+            ],
+            productions: vec![],
+            initial_symbol: "E".to_string(),
+        };
+
+        grammar.add_production(Production::new("E", "TZ".to_string()).unwrap());
+        grammar.add_production(Production::new("Z", "+TZ | £".to_string()).unwrap());
+        grammar.add_production(Production::new("T", "FY".to_string()).unwrap());
+        grammar.add_production(Production::new("Y", "*FY | £".to_string()).unwrap());
+        grammar.add_production(Production::new("F", "(E) | id".to_string()).unwrap());
+
+        grammar.compute_follows();
+
+        let table = ParseTable::new(&grammar).unwrap();
+        let parser = PredictiveParser::new(&grammar, &table);
+
+        assert_eq!(
+            parser.parse(&[Token::Terminal("id".to_string())]),
+            Err(ParseError::MissingDollarSign)
+        );
+        assert_eq!(
+            parser.parse(&input(vec!["id", "-", "id"])),
+            Err(ParseError::InvalidToken {
+                position: 1,
+                token: Token::Terminal("-".to_string()),
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_tree_precedence() {
+        let mut grammar = Grammar {
+            variables: vec![
+                "E".to_string(),
+                "Z".to_string(),
+                "T".to_string(),
+                "Y".to_string(),
+                "F".to_string(),
+            ],
+            terminals: vec![
+                "+".to_string(),
+                "*".to_string(),
+                "(".to_string(),
+                "id".to_string(),
+                ")".to_string(),
+            ],
+            productions: vec![],
+            initial_symbol: "E".to_string(),
+        };
+
+        grammar.add_production(Production::new("E", "TZ".to_string()).unwrap());
+        grammar.add_production(Production::new("Z", "+TZ | £".to_string()).unwrap());
+        grammar.add_production(Production::new("T", "FY".to_string()).unwrap());
+        grammar.add_production(Production::new("Y", "*FY | £".to_string()).unwrap());
+        grammar.add_production(Production::new("F", "(E) | id".to_string()).unwrap());
+
+        grammar.compute_follows();
+
+        let table = ParseTable::new(&grammar).unwrap();
+        let parser = PredictiveParser::new(&grammar, &table);
+
+        let tree = parser
+            .parse(&input(vec!["id", "+", "id", "*", "id"]))
+            .unwrap();
+
+        assert_eq!(tree.leaves(), vec!["id", "+", "id", "*", "id"]);
+
+        // the product hangs below the right operand of the sum
+        let sum = tree.iter().find(|node| node.label() == "Z -> +TZ").unwrap();
+        let right_operand = &sum.children()[1];
+        assert_eq!(right_operand.variable(), Some("T"));
+        assert_eq!(right_operand.leaves(), vec!["id", "*", "id"]);
+    }
+
+    #[test]
+    fn test_parse_tree_display() {
+        let mut grammar = Grammar {
+            variables: vec![
+                "E".to_string(),
+                "Z".to_string(),
+                "T".to_string(),
+                "Y".to_string(),
+                "F".to_string(),
+            ],
+            terminals: vec![
+                "+".to_string(),
+                "*".to_string(),
+                "(".to_string(),
+                "id".to_string(),
+                ")".to_string(),
+            ],
+            productions: vec![],
+            initial_symbol: "E".to_string(),
+        };
+
+        grammar.add_production(Production::new("E", "TZ".to_string()).unwrap());
+        grammar.add_production(Production::new("Z", "+TZ | £".to_string()).unwrap());
+        grammar.add_production(Production::new("T", "FY".to_string()).unwrap());
+        grammar.add_production(Production::new("Y", "*FY | £".to_string()).unwrap());
+        grammar.add_production(Production::new("F", "(E) | id".to_string()).unwrap());
+
+        grammar.compute_follows();
+
+        let table = ParseTable::new(&grammar).unwrap();
+        let parser = PredictiveParser::new(&grammar, &table);
+
+        let tree = parser.parse(&input(vec!["id"])).unwrap();
+        let expected = "E -> TZ\n\
+                        |-- T -> FY\n\
+                        |   |-- F -> id\n\
+                        |   |   `-- id\n\
+                        |   `-- Y -> £\n\
+                        |       `-- £\n\
+                        `-- Z -> £\n\
+                        \x20   `-- £\n";
+
+        assert_eq!(tree.to_string(), expected);
+    }
+
+    #[test]
+    fn test_trace() {
+        let mut grammar = Grammar {
+            variables: vec![
+                "E".to_string(),
+                "Z".to_string(),
+                "T".to_string(),
+                "Y".to_string(),
+                "F".to_string(),
+            ],
+            terminals: vec![
+                "+".to_string(),
+                "*".to_string(),
+                "(".to_string(),
+                "id".to_string(),
+                ")".to_string(),
+            ],
+            productions: vec![],
+            initial_symbol: "E".to_string(),
+        };
+
+        grammar.add_production(Production::new("E", "TZ".to_string()).unwrap());
+        grammar.add_production(Production::new("Z", "+TZ | £".to_string()).unwrap());
+        grammar.add_production(Production::new("T", "FY".to_string()).unwrap());
+        grammar.add_production(Production::new("Y", "*FY | £".to_string()).unwrap());
+        grammar.add_production(Production::new("F", "(E) | id".to_string()).unwrap());
+
+        grammar.compute_follows();
+
+        let table = ParseTable::new(&grammar).unwrap();
+        let parser = PredictiveParser::new(&grammar, &table);
+
+        let trace = parser.trace(&input(vec!["id", "+", "id"]));
+        let actions: Vec<String> = trace.steps.iter().map(|s| s.action.to_string()).collect();
+
+        assert!(trace.accepted());
+        assert_eq!(
+            actions,
+            vec![
+                "expand E -> TZ",
+                "expand T -> FY",
+                "expand F -> id",
+                "match id",
+                "expand Y -> £",
+                "expand Z -> +TZ",
+                "match +",
+                "expand T -> FY",
+                "expand F -> id",
+                "match id",
+                "expand Y -> £",
+                "expand Z -> £",
+                "accept",
+            ]
+        );
+        assert_eq!(
+            trace.steps[6].to_string(),
+            "|    $ Z T +    |    + id $     |    match +    |"
+        );
+    }
+
+    #[test]
+    fn test_trace_error() {
+        let mut grammar = Grammar {
+            variables: vec![
+                "E".to_string(),
+                "Z".to_string(),
+                "T".to_string(),
+                "Y".to_string(),
+                "F".to_string(),
+            ],
+            terminals: vec![
+                "+".to_string(),
+                "*".to_string(),
+                "(".to_string(),
+                "id".to_string(),
+                ")".to_string(),
+            ],
+            productions: vec![],
+            initial_symbol: "E".to_string(),
+        };
+
+        grammar.add_production(Production::new("E", "TZ".to_string()).unwrap());
+        grammar.add_production(Production::new("Z", "+TZ | £".to_string()).unwrap());
+        grammar.add_production(Production::new("T", "FY".to_string()).unwrap());
+        grammar.add_production(Production::new("Y", "*FY | £".to_string()).unwrap());
+        grammar.add_production(Production::new("F", "(E) | id".to_string()).unwrap());
+
+        grammar.compute_follows();
+
+        let table = ParseTable::new(&grammar).unwrap();
+        let parser = PredictiveParser::new(&grammar, &table);
+
+        let trace = parser.trace(&input(vec!["id", "+"]));
+        let last = trace.steps.last().unwrap();
+
+        assert!(!trace.accepted());
+        assert_eq!(last.action.to_string(), "error");
+        assert_eq!(
+            last.stack,
+            vec![
+                Token::DollarSign,
+                Token::Variable("Z".to_string()),
+                Token::Variable("T".to_string())
+            ]
+        );
+        assert_eq!(
+            trace.errors(),
+            vec![&parser.parse(&input(vec!["id", "+"])).unwrap_err()]
+        );
+    }
+
+    #[test]
+    fn test_panic_mode_recovery() {
+        let mut grammar = Grammar {
+            variables: vec![
+                "E".to_string(),
+                "Z".to_string(),
+                "T".to_string(),
+                "Y".to_string(),
+                "F".to_string(),
+            ],
+            terminals: vec![
+                "+".to_string(),
+                "*".to_string(),
+                "(".to_string(),
+                "id".to_string(),
+                ")".to_string(),
+            ],
+            productions: vec![],
+            initial_symbol: "E".to_string(),
+        };
+
+        grammar.add_production(Production::new("E", "TZ".to_string()).unwrap());
+        grammar.add_production(Production::new("Z", "+TZ | £".to_string()).unwrap());
+        grammar.add_production(Production::new("T", "FY".to_string()).unwrap());
+        grammar.add_production(Production::new("Y", "*FY | £".to_string()).unwrap());
+        grammar.add_production(Production::new("F", "(E) | id".to_string()).unwrap());
+
+        grammar.compute_follows();
+
+        let table = ParseTable::new(&grammar).unwrap();
+        let parser = PredictiveParser::new(&grammar, &table);
+        let first_of_f = vec![
+            Token::Terminal("(".to_string()),
+            Token::Terminal("id".to_string()),
+        ];
+
+        let errors: Vec<ParseError> = parser
+            .parse_with_recovery(&input(vec![")", "id", "*", "+", "id"]))
+            .unwrap_err()
+            .into_iter()
+            .map(|e| e.error)
+            .collect();
+
+        assert_eq!(
+            errors,
+            vec![
+                ParseError::UnexpectedToken {
+                    position: 0,
+                    found: Token::Terminal(")".to_string()),
+                    expected: first_of_f.clone(),
+                },
+                ParseError::UnexpectedToken {
+                    position: 3,
+                    found: Token::Terminal("+".to_string()),
+                    expected: first_of_f,
+                },
+            ]
+        );
+
+        let trace = parser.trace_with_recovery(&input(vec![")", "id", "*", "+", "id"]));
+        let recoveries: Vec<String> = trace
+            .steps
+            .iter()
+            .map(|s| s.action.to_string())
+            .filter(|a| a.starts_with("skip") || a.starts_with("pop"))
+            .collect();
+
+        assert!(trace.completed());
+        assert!(!trace.accepted());
+        assert_eq!(recoveries, vec!["skip )", "pop F"]);
+    }
+
+    #[test]
+    fn test_recovery_collects_every_error() {
+        let mut grammar = Grammar {
+            variables: vec![
+                "E".to_string(),
+                "Z".to_string(),
+                "T".to_string(),
+                "Y".to_string(),
+                "F".to_string(),
+            ],
+            terminals: vec![
+                "+".to_string(),
+                "*".to_string(),
+                "(".to_string(),
+                "id".to_string(),
+                ")".to_string(),
+            ],
+            productions: vec![],
+            initial_symbol: "E".to_string(),
+        };
+
+        grammar.add_production(Production::new("E", "TZ".to_string()).unwrap());
+        grammar.add_production(Production::new("Z", "+TZ | £".to_string()).unwrap());
+        grammar.add_production(Production::new("T", "FY".to_string()).unwrap());
+        grammar.add_production(Production::new("Y", "*FY | £".to_string()).unwrap());
+        grammar.add_production(Production::new("F", "(E) | id".to_string()).unwrap());
+
+        grammar.compute_follows();
+
+        let table = ParseTable::new(&grammar).unwrap();
+        let parser = PredictiveParser::new(&grammar, &table);
+
+        let errors: Vec<ParseError> = parser
+            .parse_with_recovery(&input(vec!["id", "#", "+", "(", "id"]))
+            .unwrap_err()
+            .into_iter()
+            .map(|e| e.error)
+            .collect();
+
+        assert_eq!(
+            errors,
+            vec![
+                ParseError::InvalidToken {
+                    position: 1,
+                    token: Token::Terminal("#".to_string()),
+                },
+                ParseError::UnexpectedToken {
+                    position: 5,
+                    found: Token::DollarSign,
+                    expected: vec![Token::Terminal(")".to_string())],
+                },
+            ]
+        );
+        assert!(parser
+            .parse_with_recovery(&input(vec!["(", "id", ")"]))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_phrase_level_recovery() {
+        let mut grammar = Grammar {
+            variables: vec![
+                "E".to_string(),
+                "Z".to_string(),
+                "T".to_string(),
+                "Y".to_string(),
+                "F".to_string(),
+            ],
+            terminals: vec![
+                "+".to_string(),
+                "*".to_string(),
+                "(".to_string(),
+                "id".to_string(),
+                ")".to_string(),
+            ],
+            productions: vec![],
+            initial_symbol: "E".to_string(),
+        };
+
+        grammar.add_production(Production::new("E", "TZ".to_string()).unwrap());
+        grammar.add_production(Production::new("Z", "+TZ | £".to_string()).unwrap());
+        grammar.add_production(Production::new("T", "FY".to_string()).unwrap());
+        grammar.add_production(Production::new("Y", "*FY | £".to_string()).unwrap());
+        grammar.add_production(Production::new("F", "(E) | id".to_string()).unwrap());
+
+        grammar.compute_follows();
+
+        let mut table = ParseTable::new(&grammar).unwrap();
+        let plus = Token::Terminal("+".to_string());
+        let times = Token::Terminal("*".to_string());
+        let id = Token::Terminal("id".to_string());
+
+        table
+            .set_error_handler("E", plus.clone(), ErrorHandler::Delete)
+            .unwrap();
+        table
+            .set_error_handler("T", times, ErrorHandler::Insert(id.clone()))
+            .unwrap();
+        let parser = PredictiveParser::new(&grammar, &table);
+
+        let errors = parser
+            .parse_with_recovery(&input(vec!["+", "id", "+", "*", "id"]))
+            .unwrap_err();
+        let repairs: Vec<Option<Repair>> = errors.iter().map(|e| e.repair.clone()).collect();
+
+        assert_eq!(
+            repairs,
+            vec![
+                Some(Repair::Delete {
+                    position: 0,
+                    token: plus,
+                }),
+                Some(Repair::Insert {
+                    position: 3,
+                    token: id,
+                }),
+            ]
+        );
+        assert_eq!(
+            errors[1].to_string(),
+            "unexpected '*' at position 3, expected one of: (,id, suggestion: insert 'id' at position 3"
+        );
+
+        let trace = parser.trace_with_recovery(&input(vec!["+", "id", "+", "*", "id"]));
+        assert!(trace.completed());
+        assert!(!trace.accepted());
+        assert_eq!(trace.errors().len(), 2);
+    }
+
+    #[test]
+    fn test_phrase_level_repairs_once_per_position() {
+        let mut grammar = Grammar {
+            variables: vec![
+                "E".to_string(),
+                "Z".to_string(),
+                "T".to_string(),
+                "Y".to_string(),
+                "F".to_string(),
+            ],
+            terminals: vec![
+                "+".to_string(),
+                "*".to_string(),
+                "(".to_string(),
+                "id".to_string(),
+                ")".to_string(),
+            ],
+            productions: vec![],
+            initial_symbol: "E".to_string(),
+        };
+
+        grammar.add_production(Production::new("E", "TZ".to_string()).unwrap());
+        grammar.add_production(Production::new("Z", "+TZ | £".to_string()).unwrap());
+        grammar.add_production(Production::new("T", "FY".to_string()).unwrap());
+        grammar.add_production(Production::new("Y", "*FY | £".to_string()).unwrap());
+        grammar.add_production(Production::new("F", "(E) | id".to_string()).unwrap());
+
+        grammar.compute_follows();
+
+        let mut table = ParseTable::new(&grammar).unwrap();
+        let plus = Token::Terminal("+".to_string());
+
+        table
+            .set_error_handler("E", plus.clone(), ErrorHandler::Insert(plus.clone()))
+            .unwrap();
+        let parser = PredictiveParser::new(&grammar, &table);
+
+        let errors = parser
+            .parse_with_recovery(&input(vec!["+", "id"]))
+            .unwrap_err();
+
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].repair.is_some());
+        assert_eq!(errors[1].repair, None);
+    }
+
+    #[test]
+    fn test_error_handler_on_filled_cell() {
+        let mut grammar = Grammar {
+            variables: vec![
+                "E".to_string(),
+                "Z".to_string(),
+                "T".to_string(),
+                "Y".to_string(),
+                "F".to_string(),
+            ],
+            terminals: vec![
+                "+".to_string(),
+                "*".to_string(),
+                "(".to_string(),
+                "id".to_string(),
+                ")".to_string(),
+            ],
+            productions: vec![],
+            initial_symbol: "E".to_string(),
+        };
+
+        grammar.add_production(Production::new("E", "TZ".to_string()).unwrap());
+        grammar.add_production(Production::new("Z", "+TZ | £".to_string()).unwrap());
+        grammar.add_production(Production::new("T", "FY".to_string()).unwrap());
+        grammar.add_production(Production::new("Y", "*FY | £".to_string()).unwrap());
+        grammar.add_production(Production::new("F", "(E) | id".to_string()).unwrap());
+
+        grammar.compute_follows();
+
+        let mut table = ParseTable::new(&grammar).unwrap();
+
+        assert!(table
+            .set_error_handler("E", Token::Terminal("id".to_string()), ErrorHandler::Delete)
+            .is_err());
+        assert_eq!(
+            table.set_error_handler("E", Token::DollarSign, ErrorHandler::Insert(Token::Epsilon)),
+            Err(TableError::InvalidInsertion(Token::Epsilon))
+        );
+
+        let unknown = Token::Terminal("zzz".to_string());
+        assert_eq!(
+            table.set_error_handler(
+                "E",
+                Token::Terminal("+".to_string()),
+                ErrorHandler::Insert(unknown.clone())
+            ),
+            Err(TableError::InvalidInsertion(unknown))
+        );
+    }
+}
@@ -1,43 +1,47 @@
+use crate::grammar::GrammarError;
 use crate::token::{Token, EPSILON};
 use std::collections::HashSet;
-use std::fmt;
 
+// only grammars are serialized and displayed, the sets follow their terminal declarations
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
 pub struct Production {
-    pub variable: char,
+    pub variable: String,
     pub derivation: String,
+    #[cfg_attr(feature = "serde", serde(default))]
     pub firsts: HashSet<Token>,
+    #[cfg_attr(feature = "serde", serde(default))]
     pub follows: HashSet<Token>,
 }
 
 impl Production {
-    pub fn new(variable: char, derivation: String) -> Result<Production, String> {
-        if variable.is_uppercase() {
+    pub fn new(variable: &str, derivation: String) -> Result<Production, GrammarError> {
+        if Self::is_valid_variable(variable) {
             return Ok(Production {
-                variable,
-                derivation: derivation.replace(' ', ""),
+                variable: variable.to_string(),
+                derivation: Self::normalize_derivation(&derivation),
                 firsts: HashSet::new(),
                 follows: HashSet::new(),
             });
         }
 
-        Err(format!(
-            "the variable '{}' must be a uppercase character.",
-            variable
-        ))
+        Err(GrammarError::InvalidVariable(variable.to_string()))
     }
 
-    pub fn ends_with_variable(&self, variable: char) -> bool {
-        self.derivation.ends_with(variable)
+    pub fn is_valid_variable(variable: &str) -> bool {
+        !variable.is_empty()
+            && !variable.contains(char::is_whitespace)
+            && !variable.contains('|')
+            && !variable.contains(EPSILON)
     }
 
-    pub fn ends_with_token(&self, token: &Token) -> bool {
-        match token {
-            Token::Variable(ch) => self.derivation.ends_with(&ch.to_string()),
-            Token::Terminal(s) => self.derivation.ends_with(s),
-            Token::Epsilon => self.derivation.ends_with(&EPSILON),
-            _ => false,
-        }
+    // whitespace is kept to separate multi-character symbols, but only a single space each
+    fn normalize_derivation(derivation: &str) -> String {
+        derivation
+            .split('|')
+            .map(|slice| slice.split_whitespace().collect::<Vec<&str>>().join(" "))
+            .collect::<Vec<String>>()
+            .join(" | ")
     }
 
     pub fn set_firsts(&mut self, firsts: HashSet<Token>) {
@@ -52,27 +56,11 @@ impl Production {
         self.derivation.split("|")
     }
 
-    fn token_hashset_as_string(hashset: &HashSet<Token>) -> String {
-        let mut result = String::new();
-
-        hashset.iter().enumerate().for_each(|(i, v)| {
-            result += if i > 0 { "," } else { "" };
-            result += &v.to_string()
-        });
-
-        result
-    }
-}
-
-impl fmt::Display for Production {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "|{:^15}|{:^15}|{:^15}|{:^15}|",
-            self.variable,
-            self.derivation,
-            Self::token_hashset_as_string(&self.firsts),
-            Self::token_hashset_as_string(&self.follows)
-        )
+    pub(crate) fn tokens_as_string(tokens: &[Token]) -> String {
+        tokens
+            .iter()
+            .map(|t| t.to_string())
+            .collect::<Vec<String>>()
+            .join(",")
     }
 }
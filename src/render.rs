@@ -0,0 +1,383 @@
+use crate::grammar::Grammar;
+use crate::production::Production;
+use crate::token::EPSILON;
+
+const HEADERS: [&str; 4] = ["Variable", "Derivation", "Firsts", "Follows"];
+
+/// Turns a table of plain cells into one output format.
+pub trait Renderer {
+    fn render(&self, headers: &[&str], rows: &[Vec<String>]) -> String;
+}
+
+/// Boxed ASCII table whose columns grow with their content, never narrower than 15.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PlainText;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Markdown;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Csv;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Html;
+
+/// A `tabular` with the rules of the booktabs package.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Latex;
+
+/// The renderer for a format name: `plain`, `markdown`, `csv`, `html` or `latex`.
+pub fn renderer(name: &str) -> Option<Box<dyn Renderer>> {
+    match name.to_lowercase().as_str() {
+        "plain" | "text" | "txt" => Some(Box::new(PlainText)),
+        "markdown" | "md" => Some(Box::new(Markdown)),
+        "csv" => Some(Box::new(Csv)),
+        "html" => Some(Box::new(Html)),
+        "latex" | "tex" => Some(Box::new(Latex)),
+        _ => None,
+    }
+}
+
+impl Renderer for PlainText {
+    fn render(&self, headers: &[&str], rows: &[Vec<String>]) -> String {
+        let widths: Vec<usize> = (0..headers.len())
+            .map(|column| {
+                rows.iter()
+                    .map(|row| row[column].chars().count())
+                    .chain(std::iter::once(headers[column].chars().count()))
+                    .fold(15, usize::max)
+            })
+            .collect();
+
+        let divider: String = widths
+            .iter()
+            .map(|width| format!("*{:-<1$}", "", width))
+            .collect::<String>()
+            + "*\n";
+        let line = |cells: Vec<&str>| {
+            cells
+                .iter()
+                .zip(widths.iter())
+                .map(|(cell, width)| format!("|{:^1$}", cell, width))
+                .collect::<String>()
+                + "|\n"
+        };
+
+        let mut output = divider.clone();
+        output += &line(headers.to_vec());
+        output += &divider;
+        for row in rows.iter() {
+            output += &line(row.iter().map(|c| c.as_str()).collect());
+        }
+        output += &divider;
+
+        output
+    }
+}
+
+impl Renderer for Markdown {
+    fn render(&self, headers: &[&str], rows: &[Vec<String>]) -> String {
+        let line = |cells: Vec<String>| format!("| {} |\n", cells.join(" | "));
+
+        let mut output = line(headers.iter().map(|h| markdown_escape(h)).collect());
+        output += &line(headers.iter().map(|_| "---".to_string()).collect());
+        for row in rows.iter() {
+            output += &line(row.iter().map(|c| markdown_escape(c)).collect());
+        }
+
+        output
+    }
+}
+
+fn markdown_escape(cell: &str) -> String {
+    let mut escaped = String::new();
+
+    for ch in cell.chars() {
+        if "\\`*_[]<>|#".contains(ch) {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+    }
+
+    escaped
+}
+
+impl Renderer for Csv {
+    fn render(&self, headers: &[&str], rows: &[Vec<String>]) -> String {
+        let line = |cells: Vec<&str>| {
+            cells
+                .iter()
+                .map(|cell| csv_escape(cell))
+                .collect::<Vec<String>>()
+                .join(",")
+                + "\r\n"
+        };
+
+        let mut output = line(headers.to_vec());
+        for row in rows.iter() {
+            output += &line(row.iter().map(|c| c.as_str()).collect());
+        }
+
+        output
+    }
+}
+
+// quoted as in RFC 4180 when the cell holds a comma, a quote or a line break
+fn csv_escape(cell: &str) -> String {
+    if cell.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
+
+impl Renderer for Html {
+    fn render(&self, headers: &[&str], rows: &[Vec<String>]) -> String {
+        let line = |tag: &str, cells: Vec<&str>| {
+            let cells: String = cells
+                .iter()
+                .map(|cell| format!("<{0}>{1}</{0}>", tag, html_escape(cell)))
+                .collect();
+            format!("    <tr>{}</tr>\n", cells)
+        };
+
+        let mut output = String::from("<table>\n  <thead>\n");
+        output += &line("th", headers.to_vec());
+        output += "  </thead>\n  <tbody>\n";
+        for row in rows.iter() {
+            output += &line("td", row.iter().map(|c| c.as_str()).collect());
+        }
+        output += "  </tbody>\n</table>\n";
+
+        output
+    }
+}
+
+fn html_escape(cell: &str) -> String {
+    cell.chars()
+        .map(|ch| match ch {
+            '&' => "&amp;".to_string(),
+            '<' => "&lt;".to_string(),
+            '>' => "&gt;".to_string(),
+            '"' => "&quot;".to_string(),
+            '\'' => "&#39;".to_string(),
+            ch => ch.to_string(),
+        })
+        .collect()
+}
+
+impl Renderer for Latex {
+    fn render(&self, headers: &[&str], rows: &[Vec<String>]) -> String {
+        let line = |cells: Vec<&str>| {
+            let cells: Vec<String> = cells.iter().map(|cell| latex_escape(cell)).collect();
+            format!("{} \\\\\n", cells.join(" & "))
+        };
+
+        let mut output = format!("\\begin{{tabular}}{{{}}}\n", "l".repeat(headers.len()));
+        output += "\\toprule\n";
+        output += &line(headers.to_vec());
+        output += "\\midrule\n";
+        for row in rows.iter() {
+            output += &line(row.iter().map(|c| c.as_str()).collect());
+        }
+        output += "\\bottomrule\n\\end{tabular}\n";
+
+        output
+    }
+}
+
+// epsilon is typeset as such instead of the pound sign used to type it
+fn latex_escape(cell: &str) -> String {
+    let mut escaped = String::new();
+
+    for ch in cell.chars() {
+        match ch {
+            '\\' => escaped += "\\textbackslash{}",
+            '~' => escaped += "\\textasciitilde{}",
+            '^' => escaped += "\\textasciicircum{}",
+            '<' => escaped += "\\textless{}",
+            '>' => escaped += "\\textgreater{}",
+            '|' => escaped += "\\textbar{}",
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(ch);
+            }
+            ch if EPSILON.starts_with(ch) => escaped += "$\\varepsilon$",
+            ch => escaped.push(ch),
+        }
+    }
+
+    escaped
+}
+
+impl Grammar {
+    /// The variable, derivation, firsts and follows of each production.
+    pub fn first_follow_rows(&self) -> Vec<Vec<String>> {
+        self.productions
+            .iter()
+            .map(|p| {
+                vec![
+                    p.variable.clone(),
+                    p.derivation.clone(),
+                    Production::tokens_as_string(&self.ordered_tokens(&p.firsts)),
+                    Production::tokens_as_string(&self.ordered_tokens(&p.follows)),
+                ]
+            })
+            .collect()
+    }
+
+    pub fn render(&self, renderer: &dyn Renderer) -> String {
+        renderer.render(&HEADERS, &self.first_follow_rows())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::grammar::Grammar;
+    use crate::production::Production;
+    use crate::render::{renderer, Csv, Html, Latex, Markdown, PlainText};
+
+    #[test]
+    fn test_plain_text_grows_with_content() {
+        let mut grammar = Grammar {
+            variables: vec!["S".to_string(), "A_1".to_string()],
+            terminals: vec!["&".to_string(), "<".to_string()],
+            productions: vec![],
+            initial_symbol: "S".to_string(),
+        };
+
+        grammar.add_production(Production::new("S", "A_1 &".to_string()).unwrap());
+        grammar.add_production(Production::new("A_1", "< | £".to_string()).unwrap());
+        grammar.compute_follows();
+
+        grammar.productions[0].derivation = "A_1 & A_1 & A_1 &".to_string();
+
+        let expected = "\
+*---------------*-----------------*---------------*---------------*
+|   Variable    |   Derivation    |    Firsts     |    Follows    |
+*---------------*-----------------*---------------*---------------*
+|       S       |A_1 & A_1 & A_1 &|      &,<      |       $       |
+|      A_1      |      < | £      |      <,£      |       &       |
+*---------------*-----------------*---------------*---------------*
+";
+
+        assert_eq!(grammar.render(&PlainText), expected);
+        assert_eq!(grammar.to_string(), expected);
+    }
+
+    #[test]
+    fn test_markdown() {
+        let mut grammar = Grammar {
+            variables: vec!["S".to_string(), "A_1".to_string()],
+            terminals: vec!["&".to_string(), "<".to_string()],
+            productions: vec![],
+            initial_symbol: "S".to_string(),
+        };
+
+        grammar.add_production(Production::new("S", "A_1 &".to_string()).unwrap());
+        grammar.add_production(Production::new("A_1", "< | £".to_string()).unwrap());
+        grammar.compute_follows();
+
+        assert_eq!(
+            grammar.render(&Markdown),
+            "\
+| Variable | Derivation | Firsts | Follows |
+| --- | --- | --- | --- |
+| S | A\\_1 & | &,\\< | $ |
+| A\\_1 | \\< \\| £ | \\<,£ | & |
+"
+        );
+    }
+
+    #[test]
+    fn test_csv() {
+        let mut grammar = Grammar {
+            variables: vec!["S".to_string(), "A_1".to_string()],
+            terminals: vec!["&".to_string(), "<".to_string()],
+            productions: vec![],
+            initial_symbol: "S".to_string(),
+        };
+
+        grammar.add_production(Production::new("S", "A_1 &".to_string()).unwrap());
+        grammar.add_production(Production::new("A_1", "< | £".to_string()).unwrap());
+        grammar.compute_follows();
+
+        grammar.productions[0].derivation = "say \"hi\", A_1".to_string();
+
+        assert_eq!(
+            grammar.render(&Csv),
+            "Variable,Derivation,Firsts,Follows\r\n\
+             S,\"say \"\"hi\"\", A_1\",\"&,<\",$\r\n\
+             A_1,< | £,\"<,£\",&\r\n"
+        );
+    }
+
+    #[test]
+    fn test_html() {
+        let mut grammar = Grammar {
+            variables: vec!["S".to_string(), "A_1".to_string()],
+            terminals: vec!["&".to_string(), "<".to_string()],
+            productions: vec![],
+            initial_symbol: "S".to_string(),
+        };
+
+        grammar.add_production(Production::new("S", "A_1 &".to_string()).unwrap());
+        grammar.add_production(Production::new("A_1", "< | £".to_string()).unwrap());
+        grammar.compute_follows();
+
+        let html = grammar.render(&Html);
+
+        assert!(html.starts_with("<table>\n  <thead>\n    <tr><th>Variable</th>"));
+        assert!(html
+            .contains("    <tr><td>S</td><td>A_1 &amp;</td><td>&amp;,&lt;</td><td>$</td></tr>\n"));
+        assert!(html.ends_with("  </tbody>\n</table>\n"));
+    }
+
+    #[test]
+    fn test_latex() {
+        let mut grammar = Grammar {
+            variables: vec!["S".to_string(), "A_1".to_string()],
+            terminals: vec!["&".to_string(), "<".to_string()],
+            productions: vec![],
+            initial_symbol: "S".to_string(),
+        };
+
+        grammar.add_production(Production::new("S", "A_1 &".to_string()).unwrap());
+        grammar.add_production(Production::new("A_1", "< | £".to_string()).unwrap());
+        grammar.compute_follows();
+
+        assert_eq!(
+            grammar.render(&Latex),
+            "\
+\\begin{tabular}{llll}
+\\toprule
+Variable & Derivation & Firsts & Follows \\\\
+\\midrule
+S & A\\_1 \\& & \\&,\\textless{} & \\$ \\\\
+A\\_1 & \\textless{} \\textbar{} $\\varepsilon$ & \\textless{},$\\varepsilon$ & \\& \\\\
+\\bottomrule
+\\end{tabular}
+"
+        );
+    }
+
+    #[test]
+    fn test_renderer_by_name() {
+        let mut grammar = Grammar {
+            variables: vec!["S".to_string(), "A_1".to_string()],
+            terminals: vec!["&".to_string(), "<".to_string()],
+            productions: vec![],
+            initial_symbol: "S".to_string(),
+        };
+
+        grammar.add_production(Production::new("S", "A_1 &".to_string()).unwrap());
+        grammar.add_production(Production::new("A_1", "< | £".to_string()).unwrap());
+        grammar.compute_follows();
+
+        assert_eq!(
+            grammar.render(renderer("md").unwrap().as_ref()),
+            grammar.render(&Markdown)
+        );
+        assert!(renderer("pdf").is_none());
+    }
+}
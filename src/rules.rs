@@ -0,0 +1,135 @@
+use crate::grammar::Grammar;
+use crate::production::Production;
+use crate::token::{Token, TokenProcessor, EPSILON};
+use std::collections::HashSet;
+
+// an alternative without epsilon tokens, empty when it derives epsilon, and its source text
+// when it is left as written
+#[derive(Debug, Clone)]
+pub(crate) struct Rule {
+    pub(crate) tokens: Vec<Token>,
+    pub(crate) text: Option<String>,
+}
+
+impl Rule {
+    pub(crate) fn new(tokens: Vec<Token>) -> Rule {
+        Rule {
+            tokens: tokens
+                .into_iter()
+                .filter(|t| *t != Token::Epsilon)
+                .collect(),
+            text: None,
+        }
+    }
+
+    pub(crate) fn leading_variable(&self) -> Option<&str> {
+        match self.tokens.first() {
+            Some(Token::Variable(name)) => Some(name),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn text(&self) -> String {
+        match (&self.text, self.tokens.is_empty()) {
+            (Some(text), _) => text.clone(),
+            (None, true) => EPSILON.to_string(),
+            (None, false) => self
+                .tokens
+                .iter()
+                .map(|t| t.to_string())
+                .collect::<Vec<String>>()
+                .join(" "),
+        }
+    }
+}
+
+impl Grammar {
+    // the alternatives of each variable with a production, declared variables first
+    pub(crate) fn rules(&self) -> Vec<(String, Vec<Rule>)> {
+        let processor = TokenProcessor::new(self);
+
+        self.rule_variables()
+            .into_iter()
+            .map(|variable| {
+                let rules = self
+                    .productions
+                    .iter()
+                    .filter(|p| p.variable == variable)
+                    .flat_map(|p| {
+                        processor
+                            .scan_derivation(&p.derivation)
+                            .into_iter()
+                            .zip(p.get_derivation_slices())
+                            .map(|((slice, _), text)| Rule {
+                                text: Some(text.trim().to_string()),
+                                ..Rule::new(slice.tokens)
+                            })
+                            .collect::<Vec<Rule>>()
+                    })
+                    .collect();
+
+                (variable, rules)
+            })
+            .collect()
+    }
+
+    // a grammar with the same symbols and one production per rule, in order, new variables are
+    // declared right after the variable of the previous rule
+    pub(crate) fn with_rules(&self, rules: Vec<(String, Vec<Rule>)>) -> Grammar {
+        let mut grammar = Grammar {
+            variables: self.variables.clone(),
+            terminals: self.terminals.clone(),
+            productions: vec![],
+            initial_symbol: self.initial_symbol.clone(),
+        };
+        let mut previous: Option<String> = None;
+
+        for (variable, alternatives) in rules {
+            if !grammar.variables.contains(&variable)
+                && self.get_production_by_var(&variable).is_none()
+            {
+                let position = previous
+                    .as_ref()
+                    .and_then(|previous| grammar.variables.iter().position(|v| v == previous));
+                let at = position.map_or(grammar.variables.len(), |p| p + 1);
+                grammar.variables.insert(at, variable.clone());
+            }
+
+            let derivation: Vec<String> = alternatives.iter().map(|r| r.text()).collect();
+            if !derivation.is_empty() {
+                grammar.add_production(Production::new(&variable, derivation.join(" | ")).unwrap());
+            }
+            previous = Some(variable);
+        }
+
+        grammar
+    }
+
+    // variables with a production, declared ones first
+    pub(crate) fn rule_variables(&self) -> Vec<String> {
+        let mut variables: Vec<String> = vec![];
+
+        for variable in self
+            .variables
+            .iter()
+            .chain(self.productions.iter().map(|p| &p.variable))
+        {
+            if !variables.contains(variable) && self.get_production_by_var(variable).is_some() {
+                variables.push(variable.clone());
+            }
+        }
+
+        variables
+    }
+}
+
+// the name with as many primes as needed to be unused, E', E''...
+pub(crate) fn fresh_variable(used: &mut HashSet<String>, name: &str) -> String {
+    let mut fresh = format!("{}'", name);
+    while used.contains(&fresh) {
+        fresh.push('\'');
+    }
+    used.insert(fresh.clone());
+
+    fresh
+}
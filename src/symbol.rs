@@ -0,0 +1,206 @@
+use crate::bitset::BitSet;
+use crate::grammar::{Grammar, GrammarError};
+use crate::production::Production;
+use crate::token::{DerivationTokenSlice, Token, TokenProcessor};
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum Symbol {
+    Variable(usize),
+    Terminal(usize),
+    Epsilon,
+}
+
+/// Dense ids for the variables and terminals of a grammar. The terminal ids are followed by
+/// the ids of `$` and epsilon, so FIRST and FOLLOW sets fit in one `BitSet` over terminal ids.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct SymbolTable {
+    variables: Vec<String>,
+    terminals: Vec<String>,
+    variable_ids: HashMap<String, usize>,
+    terminal_ids: HashMap<String, usize>,
+}
+
+impl SymbolTable {
+    pub fn new(grammar: &Grammar) -> SymbolTable {
+        let mut table = SymbolTable::default();
+
+        // variables with a production but no declaration still get an id
+        let variables = grammar
+            .variables
+            .iter()
+            .chain(grammar.productions.iter().map(|p| &p.variable));
+        for variable in variables {
+            if !table.variable_ids.contains_key(variable) {
+                table
+                    .variable_ids
+                    .insert(variable.clone(), table.variables.len());
+                table.variables.push(variable.clone());
+            }
+        }
+
+        for terminal in grammar.terminals.iter() {
+            if !table.terminal_ids.contains_key(terminal) {
+                table
+                    .terminal_ids
+                    .insert(terminal.clone(), table.terminals.len());
+                table.terminals.push(terminal.clone());
+            }
+        }
+
+        table
+    }
+
+    pub fn variable_count(&self) -> usize {
+        self.variables.len()
+    }
+
+    pub fn terminal_count(&self) -> usize {
+        self.terminals.len()
+    }
+
+    pub fn variable_id(&self, name: &str) -> Option<usize> {
+        self.variable_ids.get(name).copied()
+    }
+
+    pub fn terminal_id(&self, name: &str) -> Option<usize> {
+        self.terminal_ids.get(name).copied()
+    }
+
+    pub fn variable_name(&self, id: usize) -> &str {
+        &self.variables[id]
+    }
+
+    pub fn dollar_sign(&self) -> usize {
+        self.terminals.len()
+    }
+
+    pub fn epsilon(&self) -> usize {
+        self.terminals.len() + 1
+    }
+
+    /// An empty set able to hold every terminal, `$` and epsilon.
+    pub fn terminal_set(&self) -> BitSet {
+        BitSet::new(self.terminals.len() + 2)
+    }
+
+    pub fn variable_set(&self) -> BitSet {
+        BitSet::new(self.variables.len())
+    }
+
+    pub fn symbol(&self, token: &Token) -> Option<Symbol> {
+        match token {
+            Token::Variable(name) => self.variable_id(name).map(Symbol::Variable),
+            Token::Terminal(name) => self.terminal_id(name).map(Symbol::Terminal),
+            Token::Epsilon => Some(Symbol::Epsilon),
+            _ => None,
+        }
+    }
+
+    pub fn encode(&self, tokens: &[Token]) -> Vec<Symbol> {
+        tokens.iter().filter_map(|t| self.symbol(t)).collect()
+    }
+
+    /// Every alternative of the grammar as the id of its variable and its encoded symbols.
+    pub fn alternatives(&self, grammar: &Grammar) -> Vec<(usize, Vec<Symbol>)> {
+        let processor = TokenProcessor::new(grammar);
+
+        self.encode_alternatives(grammar, |p| Ok(processor.process_derivation(&p.derivation)))
+            .unwrap_or_default()
+    }
+
+    /// Like `alternatives`, failing on derivation text that is no declared symbol.
+    pub fn strict_alternatives(
+        &self,
+        grammar: &Grammar,
+    ) -> Result<Vec<(usize, Vec<Symbol>)>, GrammarError> {
+        let processor = TokenProcessor::new(grammar);
+
+        self.encode_alternatives(grammar, |p| processor.tokenize(p))
+    }
+
+    fn encode_alternatives<F>(
+        &self,
+        grammar: &Grammar,
+        tokenize: F,
+    ) -> Result<Vec<(usize, Vec<Symbol>)>, GrammarError>
+    where
+        F: Fn(&Production) -> Result<Vec<DerivationTokenSlice>, GrammarError>,
+    {
+        let mut alternatives: Vec<(usize, Vec<Symbol>)> = vec![];
+
+        for p in grammar.productions.iter() {
+            let variable = self.variable_ids[&p.variable];
+            for slice in tokenize(p)? {
+                alternatives.push((variable, self.encode(&slice.tokens)));
+            }
+        }
+
+        Ok(alternatives)
+    }
+
+    /// Id of a terminal, `$` or epsilon inside a terminal set.
+    pub fn lookahead_id(&self, token: &Token) -> Option<usize> {
+        match token {
+            Token::Terminal(name) => self.terminal_id(name),
+            Token::DollarSign => Some(self.dollar_sign()),
+            Token::Epsilon => Some(self.epsilon()),
+            _ => None,
+        }
+    }
+
+    pub fn lookahead_token(&self, id: usize) -> Token {
+        match id {
+            id if id < self.terminals.len() => Token::Terminal(self.terminals[id].clone()),
+            id if id == self.dollar_sign() => Token::DollarSign,
+            _ => Token::Epsilon,
+        }
+    }
+
+    pub fn tokens(&self, set: &BitSet) -> HashSet<Token> {
+        set.iter().map(|id| self.lookahead_token(id)).collect()
+    }
+
+    /// The tokens of the set by id, which is the order of `token::ordered_tokens`.
+    pub fn ordered_tokens(&self, set: &BitSet) -> Vec<Token> {
+        set.iter().map(|id| self.lookahead_token(id)).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::grammar::Grammar;
+    use crate::production::Production;
+    use crate::symbol::{Symbol, SymbolTable};
+    use crate::token::Token;
+
+    #[test]
+    fn test_symbol_table() {
+        let mut grammar = Grammar {
+            variables: vec!["S".to_string(), "A".to_string()],
+            terminals: vec!["a".to_string(), "b".to_string()],
+            productions: vec![],
+            initial_symbol: "S".to_string(),
+        };
+
+        grammar.add_production(Production::new("S", "aA | £".to_string()).unwrap());
+        grammar.add_production(Production::new("A", "b".to_string()).unwrap());
+
+        let table = SymbolTable::new(&grammar);
+
+        assert_eq!(table.variable_id("A"), Some(1));
+        assert_eq!(table.terminal_id("b"), Some(1));
+        assert_eq!(table.variable_id("B"), None);
+        assert_eq!(table.lookahead_id(&Token::DollarSign), Some(2));
+        assert_eq!(table.lookahead_token(3), Token::Epsilon);
+
+        assert_eq!(
+            table.alternatives(&grammar),
+            vec![
+                (0, vec![Symbol::Terminal(0), Symbol::Variable(1)]),
+                (0, vec![Symbol::Epsilon]),
+                (1, vec![Symbol::Terminal(1)]),
+            ]
+        );
+    }
+}
@@ -0,0 +1,665 @@
+use crate::analysis::Analysis;
+use crate::bitset::BitSet;
+use crate::conflict::ConflictReport;
+use crate::grammar::{Grammar, GrammarError};
+use crate::production::Production;
+use crate::token::{DerivationTokenSlice, Token, TokenProcessor};
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Alternative {
+    pub variable: String,
+    pub index: usize,
+    pub tokens: Vec<Token>,
+    // the alternative as written in the production derivation
+    pub derivation: String,
+}
+
+impl Alternative {
+    pub fn new(variable: &str, index: usize, tokens: Vec<Token>, derivation: &str) -> Alternative {
+        Alternative {
+            variable: variable.to_string(),
+            index,
+            tokens,
+            derivation: derivation.trim().to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Alternative {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} -> {}", self.variable, self.derivation)
+    }
+}
+
+/// Phrase-level routine run by the parser when it reaches an empty cell.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ErrorHandler {
+    // the terminal is assumed to be missing right before the lookahead
+    Insert(Token),
+    // the lookahead is assumed to be extra
+    Delete,
+}
+
+/// Why a table could not be built or an error handler was refused.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum TableError {
+    Grammar(GrammarError),
+    Conflicts(ConflictReport),
+    FilledCell {
+        variable: String,
+        lookahead: Token,
+        // the alternative as displayed, `E -> T Z`
+        alternative: String,
+    },
+    // only terminals of the table can be inserted
+    InvalidInsertion(Token),
+}
+
+impl fmt::Display for TableError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TableError::Grammar(e) => e.fmt(f),
+            TableError::Conflicts(report) => report.fmt(f),
+            TableError::FilledCell {
+                variable,
+                lookahead,
+                alternative,
+            } => write!(
+                f,
+                "the cell [{}, {}] already holds '{}'.",
+                variable, lookahead, alternative
+            ),
+            TableError::InvalidInsertion(token) => write!(
+                f,
+                "only terminals of the table can be inserted, found '{}'.",
+                token
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TableError {}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "TableData", from = "TableData")
+)]
+pub struct ParseTable {
+    pub variables: Vec<String>,
+    pub lookaheads: Vec<Token>,
+    alternatives: Vec<Alternative>,
+    // the index in `alternatives` of every cell, one row of lookaheads per variable
+    cells: Vec<Option<usize>>,
+    // positions in `variables` and `lookaheads`
+    rows: HashMap<String, usize>,
+    columns: HashMap<Token, usize>,
+    error_handlers: HashMap<(String, Token), ErrorHandler>,
+}
+
+// tables built in another order may number their alternatives differently
+impl PartialEq for ParseTable {
+    fn eq(&self, other: &ParseTable) -> bool {
+        self.variables == other.variables
+            && self.lookaheads == other.lookaheads
+            && self.error_handlers == other.error_handlers
+            && self.variables.iter().all(|variable| {
+                self.lookaheads.iter().all(|lookahead| {
+                    self.get(variable, lookahead) == other.get(variable, lookahead)
+                })
+            })
+    }
+}
+
+impl Eq for ParseTable {}
+
+// cells keyed by a variable and a lookahead, which JSON and TOML cannot use as map keys
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct TableData {
+    variables: Vec<String>,
+    lookaheads: Vec<Token>,
+    cells: Vec<CellData<Alternative>>,
+    #[serde(default)]
+    error_handlers: Vec<CellData<ErrorHandler>>,
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct CellData<T> {
+    variable: String,
+    lookahead: Token,
+    value: T,
+}
+
+// in table order, so the output does not depend on the hash map
+#[cfg(feature = "serde")]
+fn cell_data<T, F>(table: &ParseTable, value: F) -> Vec<CellData<T>>
+where
+    F: Fn(&str, &Token) -> Option<T>,
+{
+    let mut cells: Vec<CellData<T>> = vec![];
+
+    for variable in table.variables.iter() {
+        for lookahead in table.lookaheads.iter() {
+            if let Some(value) = value(variable, lookahead) {
+                cells.push(CellData {
+                    variable: variable.clone(),
+                    lookahead: lookahead.clone(),
+                    value,
+                });
+            }
+        }
+    }
+
+    cells
+}
+
+#[cfg(feature = "serde")]
+impl From<ParseTable> for TableData {
+    fn from(table: ParseTable) -> TableData {
+        TableData {
+            cells: cell_data(&table, |v, l| table.get(v, l).cloned()),
+            error_handlers: cell_data(&table, |v, l| table.error_handler(v, l).cloned()),
+            variables: table.variables,
+            lookaheads: table.lookaheads,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<TableData> for ParseTable {
+    fn from(data: TableData) -> ParseTable {
+        let mut table = ParseTable::empty(data.variables, data.lookaheads);
+
+        for cell in data.cells {
+            let index = match table.alternatives.iter().position(|a| *a == cell.value) {
+                Some(index) => index,
+                None => {
+                    table.alternatives.push(cell.value);
+                    table.alternatives.len() - 1
+                }
+            };
+            if let Some(at) = table.cell(&cell.variable, &cell.lookahead) {
+                table.cells[at] = Some(index);
+            }
+        }
+
+        table.error_handlers = data
+            .error_handlers
+            .into_iter()
+            .map(|c| ((c.variable, c.lookahead), c.value))
+            .collect();
+
+        table
+    }
+}
+
+impl ParseTable {
+    /// Builds the predictive table from the analysis of the grammar, each alternative fills the
+    /// cells of its firsts and, when nullable, of the variable follows.
+    /// Derivation text that is no declared symbol fails the table, and grammars that are not
+    /// LL(1) give back the report of every conflicting cell instead.
+    pub fn new(grammar: &Grammar) -> Result<ParseTable, TableError> {
+        let analysis = grammar.analyze_strict().map_err(TableError::Grammar)?;
+        let alternatives = grammar.strict_alternatives().map_err(TableError::Grammar)?;
+
+        let report = analysis.conflicts(&alternatives);
+        if !report.is_ll1() {
+            return Err(TableError::Conflicts(report));
+        }
+
+        let mut variables: Vec<String> = vec![];
+        for alternative in alternatives.iter() {
+            if !variables.contains(&alternative.variable) {
+                variables.push(alternative.variable.clone());
+            }
+        }
+
+        let mut lookaheads: Vec<Token> = grammar
+            .terminals
+            .iter()
+            .map(|t| Token::Terminal(t.clone()))
+            .collect();
+        lookaheads.push(Token::DollarSign);
+
+        let mut table = ParseTable::empty(variables, lookaheads);
+        // the column of every terminal id of the analysis and of `$`
+        let columns: Vec<usize> = (0..=analysis.symbols.dollar_sign())
+            .map(|id| table.columns[&analysis.symbols.lookahead_token(id)])
+            .collect();
+
+        for (index, lookaheads) in analysis.predict_sets().iter().enumerate() {
+            let row = table.rows[&alternatives[index].variable];
+
+            for id in lookaheads.iter() {
+                table.cells[row * table.lookaheads.len() + columns[id]] = Some(index);
+            }
+        }
+        table.alternatives = alternatives;
+
+        Ok(table)
+    }
+
+    // a table without any alternative
+    fn empty(variables: Vec<String>, lookaheads: Vec<Token>) -> ParseTable {
+        let mut rows: HashMap<String, usize> = HashMap::new();
+        for (position, variable) in variables.iter().enumerate() {
+            rows.entry(variable.clone()).or_insert(position);
+        }
+
+        let mut columns: HashMap<Token, usize> = HashMap::new();
+        for (position, lookahead) in lookaheads.iter().enumerate() {
+            columns.entry(lookahead.clone()).or_insert(position);
+        }
+
+        ParseTable {
+            alternatives: vec![],
+            cells: vec![None; variables.len() * lookaheads.len()],
+            variables,
+            lookaheads,
+            rows,
+            columns,
+            error_handlers: HashMap::new(),
+        }
+    }
+
+    fn cell(&self, variable: &str, lookahead: &Token) -> Option<usize> {
+        let row = self.rows.get(variable)?;
+        let column = self.columns.get(lookahead)?;
+
+        Some(row * self.lookaheads.len() + column)
+    }
+
+    pub fn get(&self, variable: &str, lookahead: &Token) -> Option<&Alternative> {
+        let index = (*self.cells.get(self.cell(variable, lookahead)?)?)?;
+
+        Some(&self.alternatives[index])
+    }
+
+    pub fn contains(&self, variable: &str, lookahead: &Token) -> bool {
+        self.get(variable, lookahead).is_some()
+    }
+
+    /// Attaches an error routine to an empty cell, cells holding an alternative are refused and
+    /// only terminals the table has can be inserted.
+    pub fn set_error_handler(
+        &mut self,
+        variable: &str,
+        lookahead: Token,
+        handler: ErrorHandler,
+    ) -> Result<(), TableError> {
+        if let Some(alternative) = self.get(variable, &lookahead) {
+            return Err(TableError::FilledCell {
+                variable: variable.to_string(),
+                alternative: alternative.to_string(),
+                lookahead,
+            });
+        }
+
+        if let ErrorHandler::Insert(token) = &handler {
+            if !matches!(token, Token::Terminal(_)) || !self.lookaheads.contains(token) {
+                return Err(TableError::InvalidInsertion(token.clone()));
+            }
+        }
+
+        self.error_handlers
+            .insert((variable.to_string(), lookahead), handler);
+
+        Ok(())
+    }
+
+    pub fn error_handler(&self, variable: &str, lookahead: &Token) -> Option<&ErrorHandler> {
+        self.error_handlers
+            .get(&(variable.to_string(), lookahead.clone()))
+    }
+
+    pub fn row(&self, variable: &str) -> Vec<(&Token, &Alternative)> {
+        self.lookaheads
+            .iter()
+            .filter_map(|lookahead| self.get(variable, lookahead).map(|a| (lookahead, a)))
+            .collect()
+    }
+}
+
+impl Grammar {
+    /// Every alternative of every production, numbered per variable in derivation order.
+    /// Alternatives holding text that is no declared symbol are left out, keeping their number.
+    pub fn alternatives(&self) -> Vec<Alternative> {
+        let processor = TokenProcessor::new(self);
+
+        self.collect_alternatives(|p| Ok(processor.known_alternatives(&p.derivation)))
+            .unwrap_or_default()
+    }
+
+    /// Like `alternatives`, failing on derivation text that is no declared symbol.
+    pub fn strict_alternatives(&self) -> Result<Vec<Alternative>, GrammarError> {
+        let processor = TokenProcessor::new(self);
+
+        self.collect_alternatives(|p| Ok(processor.tokenize(p)?.into_iter().map(Some).collect()))
+    }
+
+    fn collect_alternatives<F>(&self, tokenize: F) -> Result<Vec<Alternative>, GrammarError>
+    where
+        F: Fn(&Production) -> Result<Vec<Option<DerivationTokenSlice>>, GrammarError>,
+    {
+        let mut alternatives: Vec<Alternative> = vec![];
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+
+        for p in self.productions.iter() {
+            let offset = *counts.get(p.variable.as_str()).unwrap_or(&0);
+
+            let slices = tokenize(p)?;
+
+            for (index, (slice, text)) in slices
+                .into_iter()
+                .zip(p.get_derivation_slices())
+                .enumerate()
+            {
+                if let Some(slice) = slice {
+                    alternatives.push(Alternative::new(
+                        &p.variable,
+                        offset + index,
+                        slice.tokens,
+                        text,
+                    ));
+                }
+                *counts.entry(&p.variable).or_insert(0) += 1;
+            }
+        }
+
+        Ok(alternatives)
+    }
+
+    /// Lookaheads that select the alternative: its firsts and, when it derives epsilon,
+    /// the follows of its variable, in the order of `ordered_tokens`.
+    pub fn predict(&self, alternative: &Alternative) -> Vec<Token> {
+        let mut lookaheads = self.first_of(&alternative.tokens);
+
+        if lookaheads.remove(&Token::Epsilon) {
+            for p in self
+                .productions
+                .iter()
+                .filter(|p| p.variable == alternative.variable)
+            {
+                lookaheads.extend(p.follows.iter().cloned());
+            }
+        }
+
+        self.ordered_tokens(&lookaheads)
+    }
+}
+
+impl Analysis {
+    /// Lookaheads that select each alternative, in the order of `alternatives`: its firsts and,
+    /// when it derives epsilon, the follows of its variable.
+    pub fn predict_sets(&self) -> Vec<BitSet> {
+        self.alternatives
+            .iter()
+            .map(|(variable, symbols)| {
+                let mut lookaheads = self.first_of(symbols);
+                if lookaheads.remove(self.symbols.epsilon()) {
+                    lookaheads.union_with(&self.follows[*variable]);
+                }
+
+                lookaheads
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for ParseTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let divider: String = format!(
+            "*{:-<15}*{}\n",
+            "",
+            self.lookaheads
+                .iter()
+                .map(|_| format!("{:-<15}*", ""))
+                .collect::<String>()
+        );
+
+        f.write_str(&divider)?;
+        write!(f, "|{:^15}|", "Variable")?;
+        for lookahead in self.lookaheads.iter() {
+            write!(f, "{:^15}|", lookahead.to_string())?;
+        }
+        writeln!(f)?;
+        f.write_str(&divider)?;
+
+        for variable in self.variables.iter() {
+            write!(f, "|{:^15}|", variable)?;
+            for lookahead in self.lookaheads.iter() {
+                let cell = match self.get(variable, lookahead) {
+                    Some(alternative) => alternative.to_string(),
+                    None => String::new(),
+                };
+                write!(f, "{:^15}|", cell)?;
+            }
+            writeln!(f)?;
+        }
+
+        f.write_str(&divider)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::grammar::{Grammar, GrammarError};
+    use crate::production::Production;
+    use crate::table::{ParseTable, TableError};
+    use crate::token::Token;
+
+    fn cell(table: &ParseTable, variable: &str, lookahead: &str) -> Option<String> {
+        let token = match lookahead {
+            "$" => Token::DollarSign,
+            _ => Token::Terminal(lookahead.to_string()),
+        };
+
+        table.get(variable, &token).map(|a| a.to_string())
+    }
+
+    #[test]
+    fn test_parse_table() {
+        let mut grammar = Grammar {
+            variables: vec![
+                "E".to_string(),
+                "Z".to_string(),
+                "T".to_string(),
+                "Y".to_string(),
+                "F".to_string(),
+            ],
+            terminals: vec![
+                "+".to_string(),
+                "*".to_string(),
+                "(".to_string(),
+                "id".to_string(),
+                ")".to_string(),
+            ],
+            productions: vec![],
+            initial_symbol: "E".to_string(),
+        };
+
+        grammar.add_production(Production::new("E", "TZ".to_string()).unwrap());
+        grammar.add_production(Production::new("Z", "+TZ | £".to_string()).unwrap());
+        grammar.add_production(Production::new("T", "FY".to_string()).unwrap());
+        grammar.add_production(Production::new("Y", "*FY | £".to_string()).unwrap());
+        grammar.add_production(Production::new("F", "(E) | id".to_string()).unwrap());
+
+        grammar.compute_follows();
+
+        let table = ParseTable::new(&grammar).unwrap();
+
+        assert_eq!(cell(&table, "E", "("), Some("E -> TZ".to_string()));
+        assert_eq!(cell(&table, "E", "id"), Some("E -> TZ".to_string()));
+        assert_eq!(cell(&table, "E", "+"), None);
+        assert_eq!(cell(&table, "Z", "+"), Some("Z -> +TZ".to_string()));
+        assert_eq!(cell(&table, "Z", ")"), Some("Z -> £".to_string()));
+        assert_eq!(cell(&table, "Z", "$"), Some("Z -> £".to_string()));
+        assert_eq!(cell(&table, "Y", "*"), Some("Y -> *FY".to_string()));
+        assert_eq!(cell(&table, "Y", "+"), Some("Y -> £".to_string()));
+        assert_eq!(cell(&table, "Y", "("), None);
+        assert_eq!(cell(&table, "F", "("), Some("F -> (E)".to_string()));
+        assert_eq!(cell(&table, "F", "id"), Some("F -> id".to_string()));
+    }
+
+    #[test]
+    fn test_parse_table_row() {
+        let mut grammar = Grammar {
+            variables: vec![
+                "E".to_string(),
+                "Z".to_string(),
+                "T".to_string(),
+                "Y".to_string(),
+                "F".to_string(),
+            ],
+            terminals: vec![
+                "+".to_string(),
+                "*".to_string(),
+                "(".to_string(),
+                "id".to_string(),
+                ")".to_string(),
+            ],
+            productions: vec![],
+            initial_symbol: "E".to_string(),
+        };
+
+        grammar.add_production(Production::new("E", "TZ".to_string()).unwrap());
+        grammar.add_production(Production::new("Z", "+TZ | £".to_string()).unwrap());
+        grammar.add_production(Production::new("T", "FY".to_string()).unwrap());
+        grammar.add_production(Production::new("Y", "*FY | £".to_string()).unwrap());
+        grammar.add_production(Production::new("F", "(E) | id".to_string()).unwrap());
+
+        grammar.compute_follows();
+
+        let table = ParseTable::new(&grammar).unwrap();
+        let row: Vec<String> = table
+            .row("Z")
+            .iter()
+            .map(|(lookahead, alternative)| format!("{}: {}", lookahead, alternative))
+            .collect();
+
+        assert_eq!(row, vec!["+: Z -> +TZ", "): Z -> £", "$: Z -> £"]);
+    }
+
+    #[test]
+    fn test_predict_order() {
+        let mut grammar = Grammar {
+            variables: vec![
+                "E".to_string(),
+                "Z".to_string(),
+                "T".to_string(),
+                "Y".to_string(),
+                "F".to_string(),
+            ],
+            terminals: vec![
+                "+".to_string(),
+                "*".to_string(),
+                "(".to_string(),
+                "id".to_string(),
+                ")".to_string(),
+            ],
+            productions: vec![],
+            initial_symbol: "E".to_string(),
+        };
+
+        grammar.add_production(Production::new("E", "TZ".to_string()).unwrap());
+        grammar.add_production(Production::new("Z", "+TZ | £".to_string()).unwrap());
+        grammar.add_production(Production::new("T", "FY".to_string()).unwrap());
+        grammar.add_production(Production::new("Y", "*FY | £".to_string()).unwrap());
+        grammar.add_production(Production::new("F", "(E) | id".to_string()).unwrap());
+
+        grammar.compute_follows();
+
+        let alternatives = grammar.alternatives();
+        let empty = alternatives
+            .iter()
+            .find(|a| a.variable == "Y" && a.index == 1)
+            .unwrap();
+
+        for _ in 0..8 {
+            assert_eq!(
+                grammar.predict(empty),
+                vec![
+                    Token::Terminal("+".to_string()),
+                    Token::Terminal(")".to_string()),
+                    Token::DollarSign,
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_table_with_conflicts() {
+        let mut grammar = Grammar {
+            variables: vec!["E".to_string(), "T".to_string()],
+            terminals: vec!["+".to_string(), "id".to_string()],
+            productions: vec![],
+            initial_symbol: "E".to_string(),
+        };
+
+        grammar.add_production(Production::new("E", "E+T | T".to_string()).unwrap());
+        grammar.add_production(Production::new("T", "id".to_string()).unwrap());
+        grammar.compute_follows();
+
+        let report = match ParseTable::new(&grammar) {
+            Err(TableError::Conflicts(report)) => report,
+            other => panic!("expected conflicts, got {:?}", other),
+        };
+
+        assert_eq!(report.conflicts.len(), 1);
+        assert_eq!(report.conflicts[0].variable, "E");
+    }
+
+    #[test]
+    fn test_parse_table_unknown_symbol() {
+        let mut grammar = Grammar {
+            variables: vec![
+                "E".to_string(),
+                "Z".to_string(),
+                "T".to_string(),
+                "Y".to_string(),
+                "F".to_string(),
+            ],
+            terminals: vec![
+                "+".to_string(),
+                "*".to_string(),
+                "(".to_string(),
+                "id".to_string(),
+                ")".to_string(),
+            ],
+            productions: vec![],
+            initial_symbol: "E".to_string(),
+        };
+
+        grammar.add_production(Production::new("E", "TZ".to_string()).unwrap());
+        grammar.add_production(Production::new("Z", "+TZ | £".to_string()).unwrap());
+        grammar.add_production(Production::new("T", "FY".to_string()).unwrap());
+        grammar.add_production(Production::new("Y", "*FY | £".to_string()).unwrap());
+        grammar.add_production(Production::new("F", "(E) | id".to_string()).unwrap());
+
+        grammar.compute_follows();
+
+        grammar.productions[4].derivation = "(E) | id | !".to_string();
+        grammar.compute_follows();
+
+        assert_eq!(
+            ParseTable::new(&grammar),
+            Err(TableError::Grammar(GrammarError::UnknownSymbol {
+                variable: "F".to_string(),
+                symbol: "!".to_string(),
+                offset: 11,
+            }))
+        );
+
+        // the typo is no epsilon alternative, it is left out keeping the numbering
+        let alternatives = grammar.alternatives();
+        assert_eq!(alternatives.len(), 8);
+        assert!(alternatives.iter().all(|a| a.derivation != "!"));
+    }
+}
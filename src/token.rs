@@ -1,37 +1,58 @@
-use crate::grammar::Grammar;
+use crate::grammar::{Grammar, GrammarError};
+use crate::production::Production;
+use std::fmt;
 
 pub const EPSILON: &str = "£";
 pub const DOLLAR_SIGN: &str = "$";
 
 #[derive(Debug, Eq, PartialEq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(rename_all = "snake_case", tag = "kind", content = "name")
+)]
 pub enum Token {
-    Variable(char),
+    Variable(String),
     Terminal(String),
     Epsilon,
     DollarSign,
-    Placeholder(char),
 }
 
-impl ToString for Token {
-    fn to_string(&self) -> String {
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Token::Variable(ch) => {
-                let mut s = String::new();
-                s.push(ch.to_owned());
-                s
-            }
-            Token::Terminal(s) => s.to_owned(),
-            Token::Epsilon => EPSILON.to_string(),
-            Token::DollarSign => DOLLAR_SIGN.to_string(),
-            Token::Placeholder(ch) => {
-                let mut s = String::new();
-                s.push(ch.to_owned());
-                s
-            }
+            Token::Variable(name) => f.write_str(name),
+            Token::Terminal(s) => f.write_str(s),
+            Token::Epsilon => f.write_str(EPSILON),
+            Token::DollarSign => f.write_str(DOLLAR_SIGN),
         }
     }
 }
 
+/// Orders tokens the way every output lists them: terminals in the given declaration order, then
+/// terminals missing from it by name, then `$`, then epsilon.
+pub fn ordered_tokens<'a>(
+    tokens: impl IntoIterator<Item = &'a Token>,
+    terminals: &[String],
+) -> Vec<Token> {
+    let mut ordered: Vec<Token> = tokens.into_iter().cloned().collect();
+
+    ordered.sort_by_cached_key(|token| match token {
+        Token::Terminal(name) => match terminals.iter().position(|t| t == name) {
+            Some(index) => (0, index, String::new()),
+            None => (1, 0, name.clone()),
+        },
+        Token::Variable(name) => (2, 0, name.clone()),
+        Token::DollarSign => (3, 0, String::new()),
+        Token::Epsilon => (4, 0, String::new()),
+    });
+    ordered.dedup();
+
+    ordered
+}
+
+type MakeToken = fn(String) -> Token;
+
 #[derive(Debug, Eq, PartialEq)]
 pub struct DerivationTokenSlice {
     pub tokens: Vec<Token>,
@@ -49,65 +70,245 @@ pub struct TokenProcessor<'a> {
 }
 
 impl TokenProcessor<'_> {
-    pub fn new(grammar: &Grammar) -> TokenProcessor {
+    pub fn new(grammar: &Grammar) -> TokenProcessor<'_> {
         TokenProcessor { grammar }
     }
 
+    /// Alternatives holding text that is no declared symbol are left out rather than read
+    /// without it, see `tokenize` to have it reported.
     pub fn process_derivation(&self, derivation: &str) -> Vec<DerivationTokenSlice> {
-        derivation
-            .split("|")
-            .map(|slice| DerivationTokenSlice::new(self.get_token_vec(slice.trim())))
+        self.known_alternatives(derivation)
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+
+    /// Every alternative of a derivation in order, `None` for those holding text that is no
+    /// declared symbol.
+    pub fn known_alternatives(&self, derivation: &str) -> Vec<Option<DerivationTokenSlice>> {
+        self.scan_derivation(derivation)
+            .into_iter()
+            .map(|(slice, unknown)| unknown.is_empty().then_some(slice))
             .collect()
     }
 
+    /// The alternatives of a production, or the first text that is no declared symbol with its
+    /// byte offset in `Production.derivation`.
+    pub fn tokenize(
+        &self,
+        production: &Production,
+    ) -> Result<Vec<DerivationTokenSlice>, GrammarError> {
+        self.scan_derivation(&production.derivation)
+            .into_iter()
+            .map(|(slice, unknown)| match unknown.first() {
+                Some((offset, symbol)) => Err(GrammarError::UnknownSymbol {
+                    variable: production.variable.clone(),
+                    symbol: symbol.to_string(),
+                    offset: *offset,
+                }),
+                None => Ok(slice),
+            })
+            .collect()
+    }
+
+    /// The substrings of a derivation that are no declared symbol, with their byte offset.
+    pub fn unknown_symbols<'d>(&self, derivation: &'d str) -> Vec<(usize, &'d str)> {
+        self.scan_derivation(derivation)
+            .into_iter()
+            .flat_map(|(_, unknown)| unknown)
+            .collect()
+    }
+
+    /// Every alternative of a derivation with the symbols read from it and the text left
+    /// unread, by byte offset in the derivation.
+    pub fn scan_derivation<'d>(
+        &self,
+        derivation: &'d str,
+    ) -> Vec<(DerivationTokenSlice, Vec<(usize, &'d str)>)> {
+        let mut alternatives = vec![];
+        let mut offset = 0;
+
+        for slice in derivation.split('|') {
+            let (tokens, unmatched) = self.scan(slice);
+            let unknown = unmatched
+                .into_iter()
+                .map(|(at, text)| (offset + at, text))
+                .collect();
+
+            alternatives.push((DerivationTokenSlice::new(tokens), unknown));
+            offset += slice.len() + 1;
+        }
+
+        alternatives
+    }
+
+    #[cfg(test)]
     fn get_token_vec(&self, input: &str) -> Vec<Token> {
+        self.scan(input).0
+    }
+
+    // symbols are separated by whitespace or written next to each other, in which case
+    // the longest variable or terminal name wins, so `E'` is not read as `E` followed by `'`;
+    // characters starting no symbol are collected in runs with their byte offset
+    fn scan<'i>(&self, input: &'i str) -> (Vec<Token>, Vec<(usize, &'i str)>) {
         let mut tokens: Vec<Token> = vec![];
-        let mut buffer = String::new();
-
-        for ch in input.chars() {
-            buffer.push(ch);
-            if buffer.as_str().eq(EPSILON) {
-                tokens.push(Token::Epsilon);
-                buffer.clear()
-            } else if self.grammar.is_variable(&ch) {
-                tokens.push(Token::Variable(ch));
-                buffer.clear()
-            } else if self.grammar.is_terminal(&buffer) {
-                tokens.push(Token::Terminal(buffer.clone()));
-                buffer.clear()
+        let mut unmatched: Vec<(usize, &str)> = vec![];
+        let mut position = 0;
+
+        while let Some(ch) = input[position..].chars().next() {
+            if ch.is_whitespace() {
+                position += ch.len_utf8();
+                continue;
+            }
+
+            match self.longest_match(&input[position..]) {
+                Some((token, size)) => {
+                    tokens.push(token);
+                    position += size;
+                }
+                None => {
+                    let end = position + ch.len_utf8();
+                    match unmatched.last_mut() {
+                        Some((start, text)) if *start + text.len() == position => {
+                            *text = &input[*start..end];
+                        }
+                        _ => unmatched.push((position, &input[position..end])),
+                    }
+                    position = end;
+                }
+            }
+        }
+
+        (tokens, unmatched)
+    }
+
+    fn longest_match(&self, input: &str) -> Option<(Token, usize)> {
+        let mut best: Option<(MakeToken, &str)> = None;
+
+        let candidates = self
+            .grammar
+            .variables
+            .iter()
+            .map(|v| (Token::Variable as MakeToken, v.as_str()))
+            .chain(
+                self.grammar
+                    .terminals
+                    .iter()
+                    .map(|t| (Token::Terminal as MakeToken, t.as_str())),
+            )
+            .chain(std::iter::once((
+                (|_| Token::Epsilon) as MakeToken,
+                EPSILON,
+            )));
+
+        // tokens are only built for the winner, names are compared by length first
+        for (token, name) in candidates {
+            let longer = best.is_none_or(|(_, best)| name.len() > best.len());
+
+            if longer && !name.is_empty() && input.starts_with(name) {
+                best = Some((token, name));
             }
         }
 
-        tokens
+        best.map(|(token, name)| (token(name.to_string()), name.len()))
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::grammar::Grammar;
-    use crate::token::{DerivationTokenSlice, Token, TokenProcessor};
+    use crate::grammar::{Grammar, GrammarError};
+    use crate::production::Production;
+    use crate::token::{ordered_tokens, DerivationTokenSlice, Token, TokenProcessor};
 
     #[test]
     fn test_get_token_vec() {
         let grammar = Grammar {
-            variables: vec!['S', 'A', 'B'],
+            variables: vec!["S".to_string(), "A".to_string(), "B".to_string()],
             terminals: vec!["a".to_string(), "b".to_string(), "c".to_string()],
             productions: vec![],
-            initial_symbol: 'S',
+            initial_symbol: "S".to_string(),
         };
 
         let derivation_slice = String::from("bB");
-        let expected = vec![Token::Terminal("b".to_string()), Token::Variable('B')];
+        let expected = vec![
+            Token::Terminal("b".to_string()),
+            Token::Variable("B".to_string()),
+        ];
 
         let processor = TokenProcessor::new(&grammar);
 
         assert_eq!(processor.get_token_vec(&derivation_slice), expected)
     }
 
+    #[test]
+    fn test_unknown_symbols() {
+        let grammar = Grammar {
+            variables: vec!["S".to_string()],
+            terminals: vec!["a".to_string()],
+            productions: vec![],
+            initial_symbol: "S".to_string(),
+        };
+
+        let processor = TokenProcessor::new(&grammar);
+
+        assert_eq!(
+            processor.unknown_symbols("a S | xyS ? | £"),
+            vec![(6, "xy"), (10, "?")]
+        );
+        assert_eq!(
+            processor.get_token_vec("xyS"),
+            vec![Token::Variable("S".to_string())]
+        );
+        assert_eq!(
+            processor.process_derivation("a S | xyS ? | £"),
+            vec![
+                DerivationTokenSlice::new(vec![
+                    Token::Terminal("a".to_string()),
+                    Token::Variable("S".to_string()),
+                ]),
+                DerivationTokenSlice::new(vec![Token::Epsilon]),
+            ]
+        );
+        assert_eq!(processor.known_alternatives("a | ?")[1], None);
+    }
+
+    #[test]
+    fn test_tokenize() {
+        let grammar = Grammar {
+            variables: vec!["S".to_string()],
+            terminals: vec!["a".to_string()],
+            productions: vec![],
+            initial_symbol: "S".to_string(),
+        };
+        let processor = TokenProcessor::new(&grammar);
+
+        let production = Production::new("S", "a S | £".to_string()).unwrap();
+        assert_eq!(
+            processor.tokenize(&production),
+            Ok(processor.process_derivation(&production.derivation))
+        );
+
+        let production = Production::new("S", "a S | aS b".to_string()).unwrap();
+        assert_eq!(
+            processor.tokenize(&production),
+            Err(GrammarError::UnknownSymbol {
+                variable: "S".to_string(),
+                symbol: "b".to_string(),
+                offset: 9,
+            })
+        );
+    }
+
     #[test]
     fn test_get_derivations() {
         let grammar = Grammar {
-            variables: vec!['E', 'Z', 'T', 'Y', 'X'],
+            variables: vec![
+                "E".to_string(),
+                "Z".to_string(),
+                "T".to_string(),
+                "Y".to_string(),
+                "X".to_string(),
+            ],
             terminals: vec![
                 "+".to_string(),
                 "*".to_string(),
@@ -116,7 +317,7 @@ mod test {
                 ")".to_string(),
             ],
             productions: vec![],
-            initial_symbol: 'S',
+            initial_symbol: "S".to_string(),
         };
 
         let processor = TokenProcessor::new(&grammar);
@@ -127,8 +328,8 @@ mod test {
         let derivation1_slices: Vec<DerivationTokenSlice> = vec![
             DerivationTokenSlice::new(vec![
                 Token::Terminal("*".to_string()),
-                Token::Variable('X'),
-                Token::Variable('Y'),
+                Token::Variable("X".to_string()),
+                Token::Variable("Y".to_string()),
             ]),
             DerivationTokenSlice::new(vec![Token::Epsilon]),
         ];
@@ -136,7 +337,7 @@ mod test {
         let derivation2_slices: Vec<DerivationTokenSlice> = vec![
             DerivationTokenSlice::new(vec![
                 Token::Terminal("(".to_string()),
-                Token::Variable('E'),
+                Token::Variable("E".to_string()),
                 Token::Terminal(")".to_string()),
             ]),
             DerivationTokenSlice::new(vec![Token::Terminal("id".to_string())]),
@@ -145,4 +346,71 @@ mod test {
         assert_eq!(processor.process_derivation(&deriv1), derivation1_slices);
         assert_eq!(processor.process_derivation(&deriv2), derivation2_slices);
     }
+
+    #[test]
+    fn test_get_token_vec_named_variables() {
+        let grammar = Grammar {
+            variables: vec![
+                "E".to_string(),
+                "E'".to_string(),
+                "expr".to_string(),
+                "term_tail".to_string(),
+            ],
+            terminals: vec!["+".to_string(), "id".to_string()],
+            productions: vec![],
+            initial_symbol: "expr".to_string(),
+        };
+
+        let processor = TokenProcessor::new(&grammar);
+
+        assert_eq!(
+            processor.get_token_vec("+ E E'"),
+            vec![
+                Token::Terminal("+".to_string()),
+                Token::Variable("E".to_string()),
+                Token::Variable("E'".to_string()),
+            ]
+        );
+        assert_eq!(
+            processor.get_token_vec("+EE'"),
+            vec![
+                Token::Terminal("+".to_string()),
+                Token::Variable("E".to_string()),
+                Token::Variable("E'".to_string()),
+            ]
+        );
+        assert_eq!(
+            processor.get_token_vec("id term_tail expr"),
+            vec![
+                Token::Terminal("id".to_string()),
+                Token::Variable("term_tail".to_string()),
+                Token::Variable("expr".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ordered_tokens() {
+        let tokens: Vec<Token> = vec![
+            Token::Epsilon,
+            Token::Terminal("zeta".to_string()),
+            Token::DollarSign,
+            Token::Terminal("b".to_string()),
+            Token::Terminal("alpha".to_string()),
+            Token::Terminal("a".to_string()),
+        ];
+        let terminals = vec!["b".to_string(), "a".to_string()];
+
+        assert_eq!(
+            ordered_tokens(&tokens, &terminals),
+            vec![
+                Token::Terminal("b".to_string()),
+                Token::Terminal("a".to_string()),
+                Token::Terminal("alpha".to_string()),
+                Token::Terminal("zeta".to_string()),
+                Token::DollarSign,
+                Token::Epsilon,
+            ]
+        );
+    }
 }
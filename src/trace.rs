@@ -0,0 +1,111 @@
+use crate::parser::{ParseError, Repair};
+use crate::table::Alternative;
+use crate::token::Token;
+use std::fmt;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Action {
+    Expand(Alternative),
+    Match(Token),
+    Accept,
+    Error(ParseError),
+    Skip(Token),
+    Pop(Token),
+    Repair(Repair),
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Action::Expand(alternative) => write!(f, "expand {}", alternative),
+            Action::Match(token) => write!(f, "match {}", token),
+            Action::Accept => f.write_str("accept"),
+            Action::Error(_) => f.write_str("error"),
+            Action::Skip(token) => write!(f, "skip {}", token),
+            Action::Pop(token) => write!(f, "pop {}", token),
+            Action::Repair(Repair::Insert { token, .. }) => write!(f, "insert {}", token),
+            Action::Repair(Repair::Delete { token, .. }) => write!(f, "delete {}", token),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct TraceStep {
+    pub stack: Vec<Token>,
+    pub input: Vec<Token>,
+    pub action: Action,
+}
+
+impl TraceStep {
+    pub fn new(stack: &[Token], input: &[Token], action: Action) -> TraceStep {
+        TraceStep {
+            stack: stack.to_vec(),
+            input: input.to_vec(),
+            action,
+        }
+    }
+
+    // spaced, since variable and terminal names may span several characters
+    fn tokens_as_string(tokens: &[Token]) -> String {
+        tokens
+            .iter()
+            .map(|t| t.to_string())
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+}
+
+impl fmt::Display for TraceStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "|{:^15}|{:^15}|{:^15}|",
+            Self::tokens_as_string(&self.stack),
+            Self::tokens_as_string(&self.input),
+            self.action.to_string()
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct ParseTrace {
+    pub steps: Vec<TraceStep>,
+}
+
+impl ParseTrace {
+    /// The input was accepted without any syntax error.
+    pub fn accepted(&self) -> bool {
+        self.completed() && self.errors().is_empty()
+    }
+
+    /// The parse reached its accept step, possibly after recovering from errors.
+    pub fn completed(&self) -> bool {
+        matches!(self.steps.last(), Some(step) if step.action == Action::Accept)
+    }
+
+    pub fn errors(&self) -> Vec<&ParseError> {
+        self.steps
+            .iter()
+            .filter_map(|step| match &step.action {
+                Action::Error(error) => Some(error),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for ParseTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let divider: &str = &format!("*{:-<15}*{:-<15}*{:-<15}*\n", "", "", "");
+
+        f.write_str(divider)?;
+        writeln!(f, "|{:^15}|{:^15}|{:^15}|", "Stack", "Input", "Action")?;
+        f.write_str(divider)?;
+
+        for step in self.steps.iter() {
+            writeln!(f, "{}", step)?;
+        }
+
+        f.write_str(divider)
+    }
+}
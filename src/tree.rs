@@ -0,0 +1,138 @@
+use crate::table::Alternative;
+use crate::token::Token;
+use std::fmt;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ParseTree {
+    Node {
+        alternative: Alternative,
+        children: Vec<ParseTree>,
+    },
+    Leaf {
+        token: Token,
+        lexeme: String,
+    },
+    Epsilon,
+}
+
+impl ParseTree {
+    /// Rebuilds the tree rooted at the variable from the alternatives of a leftmost derivation
+    /// and the terminals they matched, both in the order the parser consumed them.
+    pub(crate) fn from_derivation<A, L>(
+        variable: &str,
+        alternatives: &mut A,
+        leaves: &mut L,
+    ) -> Option<ParseTree>
+    where
+        A: Iterator<Item = Alternative>,
+        L: Iterator<Item = Token>,
+    {
+        let alternative = alternatives.next()?;
+        if alternative.variable != variable {
+            return None;
+        }
+
+        let mut children: Vec<ParseTree> = vec![];
+        for token in alternative.tokens.iter() {
+            let child = match token {
+                Token::Variable(name) => Self::from_derivation(name, alternatives, leaves)?,
+                Token::Epsilon => ParseTree::Epsilon,
+                _ => {
+                    let token = leaves.next()?;
+                    ParseTree::Leaf {
+                        lexeme: token.to_string(),
+                        token,
+                    }
+                }
+            };
+            children.push(child);
+        }
+
+        Some(ParseTree::Node {
+            alternative,
+            children,
+        })
+    }
+
+    pub fn variable(&self) -> Option<&str> {
+        match self {
+            ParseTree::Node { alternative, .. } => Some(&alternative.variable),
+            _ => None,
+        }
+    }
+
+    pub fn children(&self) -> &[ParseTree] {
+        match self {
+            ParseTree::Node { children, .. } => children,
+            _ => &[],
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            ParseTree::Node { alternative, .. } => alternative.to_string(),
+            ParseTree::Leaf { lexeme, .. } => lexeme.clone(),
+            ParseTree::Epsilon => Token::Epsilon.to_string(),
+        }
+    }
+
+    /// Walks the tree in pre-order, every node comes before its children.
+    pub fn iter(&self) -> Iter<'_> {
+        Iter { stack: vec![self] }
+    }
+
+    /// The matched lexemes from left to right, the sentence the tree derives.
+    pub fn leaves(&self) -> Vec<&str> {
+        self.iter()
+            .filter_map(|node| match node {
+                ParseTree::Leaf { lexeme, .. } => Some(lexeme.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn write_indented(&self, f: &mut fmt::Formatter<'_>, prefix: &str, last: bool) -> fmt::Result {
+        let (branch, extension) = if last {
+            ("`-- ", "    ")
+        } else {
+            ("|-- ", "|   ")
+        };
+        writeln!(f, "{}{}{}", prefix, branch, self.label())?;
+
+        let prefix = format!("{}{}", prefix, extension);
+        let children = self.children();
+        for (index, child) in children.iter().enumerate() {
+            child.write_indented(f, &prefix, index + 1 == children.len())?;
+        }
+
+        Ok(())
+    }
+}
+
+pub struct Iter<'a> {
+    stack: Vec<&'a ParseTree>,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = &'a ParseTree;
+
+    fn next(&mut self) -> Option<&'a ParseTree> {
+        let node = self.stack.pop()?;
+        self.stack.extend(node.children().iter().rev());
+
+        Some(node)
+    }
+}
+
+impl fmt::Display for ParseTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.label())?;
+
+        let children = self.children();
+        for (index, child) in children.iter().enumerate() {
+            child.write_indented(f, "", index + 1 == children.len())?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,368 @@
+use crate::grammar::Grammar;
+use crate::rules::Rule;
+use crate::token::Token;
+use std::collections::HashSet;
+use std::fmt;
+
+/// The symbols of a grammar that take no part in deriving a sentence.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct UselessSymbols {
+    pub missing_initial_symbol: bool,
+    pub non_productive: Vec<String>,
+    // unreachable once the alternatives using non-productive variables are gone
+    pub unreachable: Vec<String>,
+    pub unused_variables: Vec<String>,
+    pub unused_terminals: Vec<String>,
+}
+
+impl UselessSymbols {
+    pub fn is_empty(&self) -> bool {
+        !self.missing_initial_symbol
+            && self.non_productive.is_empty()
+            && self.unreachable.is_empty()
+            && self.unused_variables.is_empty()
+            && self.unused_terminals.is_empty()
+    }
+}
+
+impl fmt::Display for UselessSymbols {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.missing_initial_symbol {
+            writeln!(f, "the initial symbol has no production")?;
+        }
+        for variable in self.non_productive.iter() {
+            writeln!(f, "{} never derives a string of terminals", variable)?;
+        }
+        for variable in self.unreachable.iter() {
+            writeln!(f, "{} is unreachable from the initial symbol", variable)?;
+        }
+        for variable in self.unused_variables.iter() {
+            writeln!(
+                f,
+                "the declared variable {} is not used by any production",
+                variable
+            )?;
+        }
+        for terminal in self.unused_terminals.iter() {
+            writeln!(
+                f,
+                "the declared terminal {} is not used by any production",
+                terminal
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct UselessSymbolRemoval {
+    pub grammar: Grammar,
+    pub removed: UselessSymbols,
+}
+
+impl Grammar {
+    /// Variables that appear in the grammar but derive no string of terminals.
+    pub fn non_productive_variables(&self) -> Vec<String> {
+        let rules = self.rules();
+        let productive = productive_variables(&rules);
+
+        self.used_variables(&rules)
+            .into_iter()
+            .filter(|variable| !productive.contains(variable))
+            .collect()
+    }
+
+    /// Variables with a production that no sentential form of the initial symbol contains,
+    /// once the alternatives using non-productive variables are gone.
+    pub fn unreachable_variables(&self) -> Vec<String> {
+        let rules = self.rules();
+        let productive = productive_variables(&rules);
+
+        unreachable_variables(&only_productive(&rules, &productive), &self.initial_symbol)
+    }
+
+    /// Non-productive variables, the variables unreachable once those are removed, and declared
+    /// symbols no production mentions.
+    pub fn useless_symbols(&self) -> UselessSymbols {
+        let rules = self.rules();
+        let productive = productive_variables(&rules);
+        let used = self.used_variables(&rules);
+        let terminals: HashSet<&String> = rules
+            .iter()
+            .flat_map(|(_, alternatives)| alternatives.iter())
+            .flat_map(|rule| rule.tokens.iter())
+            .filter_map(|token| match token {
+                Token::Terminal(name) => Some(name),
+                _ => None,
+            })
+            .collect();
+
+        let productive_rules = only_productive(&rules, &productive);
+
+        UselessSymbols {
+            missing_initial_symbol: !rules.iter().any(|(v, _)| *v == self.initial_symbol),
+            non_productive: used
+                .iter()
+                .filter(|variable| !productive.contains(*variable))
+                .cloned()
+                .collect(),
+            unreachable: unreachable_variables(&productive_rules, &self.initial_symbol),
+            unused_variables: self
+                .variables
+                .iter()
+                .filter(|variable| !used.contains(variable))
+                .cloned()
+                .collect(),
+            unused_terminals: self
+                .terminals
+                .iter()
+                .filter(|terminal| !terminals.contains(terminal))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// Drops the alternatives using non-productive variables, then the unreachable variables,
+    /// then the declarations left unused. A grammar whose initial symbol is non-productive
+    /// generates nothing and comes out without productions.
+    pub fn remove_useless_symbols(&self) -> UselessSymbolRemoval {
+        let removed = self.useless_symbols();
+        let rules = self.rules();
+        let productive = productive_variables(&rules);
+        let rules: Vec<(String, Vec<Rule>)> = only_productive(&rules, &productive)
+            .into_iter()
+            .filter(|(variable, _)| !removed.unreachable.contains(variable))
+            .collect();
+
+        let mut grammar = self.with_rules(rules);
+        let remaining = grammar.rules();
+        let used = grammar.used_variables(&remaining);
+        grammar.variables.retain(|variable| used.contains(variable));
+        grammar.terminals.retain(|terminal| {
+            remaining
+                .iter()
+                .flat_map(|(_, alternatives)| alternatives.iter())
+                .any(|rule| rule.tokens.contains(&Token::Terminal(terminal.clone())))
+        });
+
+        UselessSymbolRemoval { grammar, removed }
+    }
+
+    // variables with a production or mentioned in one, declared ones first
+    fn used_variables(&self, rules: &[(String, Vec<Rule>)]) -> Vec<String> {
+        let mentioned: HashSet<&String> = rules
+            .iter()
+            .flat_map(|(_, alternatives)| alternatives.iter())
+            .flat_map(|rule| rule.tokens.iter())
+            .filter_map(|token| match token {
+                Token::Variable(name) => Some(name),
+                _ => None,
+            })
+            .collect();
+        let mut used: Vec<String> = vec![];
+
+        for variable in self
+            .variables
+            .iter()
+            .chain(rules.iter().map(|(variable, _)| variable))
+        {
+            let has_rule = rules.iter().any(|(v, _)| v == variable);
+            if (has_rule || mentioned.contains(variable)) && !used.contains(variable) {
+                used.push(variable.clone());
+            }
+        }
+
+        used
+    }
+}
+
+fn productive_variables(rules: &[(String, Vec<Rule>)]) -> HashSet<String> {
+    let mut productive: HashSet<String> = HashSet::new();
+    let mut changed = true;
+
+    while changed {
+        changed = false;
+
+        for (variable, alternatives) in rules.iter() {
+            if !productive.contains(variable)
+                && alternatives
+                    .iter()
+                    .any(|rule| is_productive(rule, &productive))
+            {
+                productive.insert(variable.clone());
+                changed = true;
+            }
+        }
+    }
+
+    productive
+}
+
+fn is_productive(rule: &Rule, productive: &HashSet<String>) -> bool {
+    rule.tokens.iter().all(|token| match token {
+        Token::Variable(name) => productive.contains(name),
+        _ => true,
+    })
+}
+
+fn only_productive(
+    rules: &[(String, Vec<Rule>)],
+    productive: &HashSet<String>,
+) -> Vec<(String, Vec<Rule>)> {
+    rules
+        .iter()
+        .filter(|(variable, _)| productive.contains(variable))
+        .map(|(variable, alternatives)| {
+            let alternatives = alternatives
+                .iter()
+                .filter(|rule| is_productive(rule, productive))
+                .cloned()
+                .collect();
+            (variable.clone(), alternatives)
+        })
+        .collect()
+}
+
+fn unreachable_variables(rules: &[(String, Vec<Rule>)], initial_symbol: &str) -> Vec<String> {
+    let reachable = reachable_variables(rules, initial_symbol);
+
+    rules
+        .iter()
+        .map(|(variable, _)| variable)
+        .filter(|variable| !reachable.contains(*variable))
+        .cloned()
+        .collect()
+}
+
+fn reachable_variables(rules: &[(String, Vec<Rule>)], initial_symbol: &str) -> HashSet<String> {
+    let mut reachable: HashSet<String> = HashSet::new();
+    let mut stack: Vec<String> = vec![initial_symbol.to_string()];
+
+    while let Some(variable) = stack.pop() {
+        if !reachable.insert(variable.clone()) {
+            continue;
+        }
+
+        for (_, alternatives) in rules.iter().filter(|(v, _)| *v == variable) {
+            for token in alternatives.iter().flat_map(|rule| rule.tokens.iter()) {
+                if let Token::Variable(name) = token {
+                    stack.push(name.clone());
+                }
+            }
+        }
+    }
+
+    reachable
+}
+
+#[cfg(test)]
+mod test {
+    use crate::grammar::Grammar;
+    use crate::production::Production;
+
+    #[test]
+    fn test_useless_symbols() {
+        let mut grammar = Grammar {
+            variables: vec![
+                "S".to_string(),
+                "A".to_string(),
+                "B".to_string(),
+                "C".to_string(),
+                "D".to_string(),
+            ],
+            terminals: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            productions: vec![],
+            initial_symbol: "S".to_string(),
+        };
+
+        // B loops forever, C is only used next to B, D is declared but never used
+        grammar.add_production(Production::new("S", "a S | A | A B C".to_string()).unwrap());
+        grammar.add_production(Production::new("A", "a | £".to_string()).unwrap());
+        grammar.add_production(Production::new("B", "b B".to_string()).unwrap());
+        grammar.add_production(Production::new("C", "a".to_string()).unwrap());
+
+        assert_eq!(grammar.non_productive_variables(), vec!["B"]);
+        assert_eq!(grammar.unreachable_variables(), vec!["C"]);
+
+        let useless = grammar.useless_symbols();
+        assert_eq!(useless.unreachable, grammar.unreachable_variables());
+        assert_eq!(useless.unused_variables, vec!["D"]);
+        assert_eq!(useless.unused_terminals, vec!["c"]);
+        assert_eq!(
+            useless.to_string(),
+            "B never derives a string of terminals\n\
+             C is unreachable from the initial symbol\n\
+             the declared variable D is not used by any production\n\
+             the declared terminal c is not used by any production\n"
+        );
+    }
+
+    #[test]
+    fn test_remove_useless_symbols() {
+        let mut grammar = Grammar {
+            variables: vec![
+                "S".to_string(),
+                "A".to_string(),
+                "B".to_string(),
+                "C".to_string(),
+                "D".to_string(),
+            ],
+            terminals: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            productions: vec![],
+            initial_symbol: "S".to_string(),
+        };
+
+        // B loops forever, C is only used next to B, D is declared but never used
+        grammar.add_production(Production::new("S", "a S | A | A B C".to_string()).unwrap());
+        grammar.add_production(Production::new("A", "a | £".to_string()).unwrap());
+        grammar.add_production(Production::new("B", "b B".to_string()).unwrap());
+        grammar.add_production(Production::new("C", "a".to_string()).unwrap());
+
+        let result = grammar.remove_useless_symbols();
+
+        let derivations: Vec<(&str, &str)> = result
+            .grammar
+            .productions
+            .iter()
+            .map(|p| (p.variable.as_str(), p.derivation.as_str()))
+            .collect();
+        assert_eq!(derivations, vec![("S", "a S | A"), ("A", "a | £")]);
+        assert_eq!(result.grammar.variables, vec!["S", "A"]);
+        assert_eq!(result.grammar.terminals, vec!["a"]);
+        assert!(result.grammar.useless_symbols().is_empty());
+    }
+
+    #[test]
+    fn test_missing_initial_symbol() {
+        let mut grammar = Grammar {
+            variables: vec![
+                "S".to_string(),
+                "A".to_string(),
+                "B".to_string(),
+                "C".to_string(),
+                "D".to_string(),
+            ],
+            terminals: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            productions: vec![],
+            initial_symbol: "S".to_string(),
+        };
+
+        // B loops forever, C is only used next to B, D is declared but never used
+        grammar.add_production(Production::new("S", "a S | A | A B C".to_string()).unwrap());
+        grammar.add_production(Production::new("A", "a | £".to_string()).unwrap());
+        grammar.add_production(Production::new("B", "b B".to_string()).unwrap());
+        grammar.add_production(Production::new("C", "a".to_string()).unwrap());
+
+        grammar.initial_symbol = "Z".to_string();
+
+        let useless = grammar.useless_symbols();
+        assert!(useless.missing_initial_symbol);
+        assert_eq!(useless.unreachable, vec!["S", "A", "C"]);
+        assert!(grammar
+            .remove_useless_symbols()
+            .grammar
+            .productions
+            .is_empty());
+    }
+}
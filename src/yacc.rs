@@ -0,0 +1,450 @@
+use crate::grammar::Grammar;
+use crate::load::{
+    blank, block_end, find, lex, quoted_end, read_source, source_text, Item, Lexeme, LexemeKind,
+    LoadError, RenamedLiteral, RuleAlternative, RuleSet,
+};
+use std::collections::HashMap;
+use std::path::Path;
+
+const PUNCTUATION: [&str; 3] = [":", "|", ";"];
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct YaccGrammar {
+    pub grammar: Grammar,
+    pub literals: Vec<RenamedLiteral>,
+}
+
+impl YaccGrammar {
+    /// The text a terminal was written as, see `load::RenamedLiteral`.
+    pub fn source_text<'a>(&'a self, terminal: &'a str) -> &'a str {
+        source_text(&self.literals, terminal)
+    }
+}
+
+/// Imports the grammar of a yacc or bison file. `%token`, the precedence declarations and
+/// `%start` are read from the declarations section, the rules come from the section between
+/// the `%%` markers. Actions, the `%{ %}` prologue, `%union` and other braced code, `<type>`
+/// tags and the epilogue are skipped, as are declarations without a meaning for FIRST and
+/// FOLLOW such as `%type` or `%define`.
+///
+/// Character literals such as `'+'` become terminals, string aliases given with
+/// `%token LE "<="` are replaced by their token and `%empty` derives epsilon. Literals holding
+/// `|` or `£` are renamed, `'|'` becomes `¦`, and listed in `literals`.
+pub fn parse(input: &str) -> Result<YaccGrammar, LoadError> {
+    let lexemes = lex(&strip_code(input)?, &PUNCTUATION)?;
+    YaccParser::new(lexemes).parse()
+}
+
+pub fn read(path: impl AsRef<Path>) -> Result<YaccGrammar, LoadError> {
+    parse(&read_source(path)?)
+}
+
+impl Grammar {
+    pub fn from_yacc(input: &str) -> Result<Grammar, LoadError> {
+        parse(input).map(|yacc| yacc.grammar)
+    }
+}
+
+// blanks out the declarations code, the actions and the epilogue
+fn strip_code(input: &str) -> Result<String, LoadError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut output = String::with_capacity(input.len());
+    let mut sections = 0;
+    let (mut line, mut column) = (1, 1);
+    let mut i = 0;
+
+    while i < chars.len() {
+        let starts_with = |text: &str| {
+            text.chars()
+                .enumerate()
+                .all(|(k, c)| chars.get(i + k) == Some(&c))
+        };
+        let start = i;
+
+        if column == 1 && starts_with("%%") {
+            sections += 1;
+            i += 2;
+            output.push_str("%%");
+
+            // the epilogue is only code
+            if sections == 2 {
+                blank(&mut output, &chars[i..]);
+                break;
+            }
+        } else if starts_with("%{") {
+            i = find(&chars, i + 2, "%}").ok_or_else(|| {
+                LoadError::syntax(line, column, "'%{' is never closed".to_string())
+            })? + 2;
+            blank(&mut output, &chars[start..i]);
+        } else if chars[i] == '{' {
+            i = block_end(&chars, i).ok_or_else(|| {
+                LoadError::syntax(line, column, "'{' is never closed".to_string())
+            })?;
+            blank(&mut output, &chars[start..i]);
+        } else if sections == 0 && chars[i] == '<' {
+            i = find(&chars, i, ">").map_or(chars.len(), |end| end + 1);
+            blank(&mut output, &chars[start..i]);
+        } else if chars[i] == '\'' || chars[i] == '"' {
+            i = quoted_end(&chars, i);
+            output.extend(&chars[start..i]);
+        } else if starts_with("/*") {
+            i = find(&chars, i + 2, "*/").map_or(chars.len(), |end| end + 2);
+            output.extend(&chars[start..i]);
+        } else {
+            i += 1;
+            output.push(chars[start]);
+        }
+
+        for ch in chars[start..i].iter() {
+            if *ch == '\n' {
+                line += 1;
+                column = 1;
+            } else {
+                column += 1;
+            }
+        }
+    }
+
+    Ok(output)
+}
+
+struct YaccParser {
+    lexemes: Vec<Lexeme>,
+    position: usize,
+    rules: RuleSet,
+    aliases: HashMap<String, String>,
+}
+
+impl YaccParser {
+    fn new(lexemes: Vec<Lexeme>) -> YaccParser {
+        YaccParser {
+            lexemes: lexemes
+                .into_iter()
+                .filter(|l| l.kind != LexemeKind::Newline)
+                .collect(),
+            position: 0,
+            rules: RuleSet::default(),
+            aliases: HashMap::new(),
+        }
+    }
+
+    fn peek(&self, offset: usize) -> Option<&Lexeme> {
+        self.lexemes.get(self.position + offset)
+    }
+
+    fn end(&self) -> Lexeme {
+        self.lexemes.last().cloned().unwrap_or(Lexeme {
+            kind: LexemeKind::Newline,
+            line: 1,
+            column: 1,
+        })
+    }
+
+    fn is_marker(lexeme: &Lexeme) -> bool {
+        lexeme.kind == LexemeKind::Directive("%".to_string())
+    }
+
+    fn parse(mut self) -> Result<YaccGrammar, LoadError> {
+        self.declarations()?;
+
+        while let Some(lexeme) = self.peek(0).cloned() {
+            if Self::is_marker(&lexeme) {
+                break;
+            }
+
+            match &lexeme.kind {
+                LexemeKind::Punct(";") => self.position += 1,
+                LexemeKind::Word(variable) => {
+                    self.position += 1;
+                    match self.peek(0) {
+                        Some(Lexeme {
+                            kind: LexemeKind::Punct(":"),
+                            ..
+                        }) => self.position += 1,
+                        Some(next) => {
+                            return Err(next.error(format!(
+                                "expected ':' after '{}', found {}",
+                                variable,
+                                next.describe()
+                            )))
+                        }
+                        None => {
+                            return Err(lexeme.error(format!("expected ':' after '{}'", variable)))
+                        }
+                    }
+
+                    let alternatives = self.alternatives()?;
+                    self.rules.add_rule(&lexeme, variable, alternatives)?;
+                }
+                _ => {
+                    return Err(
+                        lexeme.error(format!("expected a rule, found {}", lexeme.describe()))
+                    )
+                }
+            }
+        }
+
+        let end = self.end();
+        let (grammar, literals) = self.rules.into_grammar(&end)?;
+
+        Ok(YaccGrammar { grammar, literals })
+    }
+
+    // everything up to the first %%
+    fn declarations(&mut self) -> Result<(), LoadError> {
+        while let Some(lexeme) = self.peek(0).cloned() {
+            self.position += 1;
+
+            let name = match &lexeme.kind {
+                LexemeKind::Directive(name) => name.clone(),
+                _ => {
+                    return Err(lexeme.error(format!(
+                        "expected a declaration, found {}",
+                        lexeme.describe()
+                    )))
+                }
+            };
+
+            if name == "%" {
+                return Ok(());
+            }
+
+            let mut arguments: Vec<Lexeme> = vec![];
+            while let Some(next) = self.peek(0) {
+                if let LexemeKind::Directive(_) = next.kind {
+                    break;
+                }
+                arguments.push(next.clone());
+                self.position += 1;
+            }
+
+            match name.as_str() {
+                "token" | "left" | "right" | "nonassoc" | "precedence" => {
+                    self.tokens(&name, &arguments)?
+                }
+                "start" => match arguments.first() {
+                    Some(Lexeme {
+                        kind: LexemeKind::Word(start),
+                        ..
+                    }) => self.rules.start = Some(start.clone()),
+                    _ => return Err(lexeme.error("'%start' needs a variable".to_string())),
+                },
+                _ => {}
+            }
+        }
+
+        Err(self
+            .end()
+            .error("missing '%%' before the rules".to_string()))
+    }
+
+    // token names, optionally followed by a number and a string alias
+    fn tokens(&mut self, directive: &str, arguments: &[Lexeme]) -> Result<(), LoadError> {
+        let mut previous: Option<String> = None;
+
+        for lexeme in arguments.iter() {
+            match &lexeme.kind {
+                LexemeKind::Word(word) if word.chars().all(|c| c.is_ascii_digit()) => {}
+                LexemeKind::Word(word) => {
+                    self.rules.declare_terminal(lexeme, word)?;
+                    previous = Some(word.clone());
+                    continue;
+                }
+                LexemeKind::Literal(text) => match previous.take() {
+                    Some(token) => {
+                        self.aliases.insert(text.clone(), token);
+                    }
+                    None if self.aliases.contains_key(text) => {}
+                    None => {
+                        let terminal = self.rules.literal(text);
+                        self.rules.declare_terminal(lexeme, &terminal)?
+                    }
+                },
+                _ => {
+                    return Err(lexeme.error(format!(
+                        "unexpected {} in '%{}'",
+                        lexeme.describe(),
+                        directive
+                    )))
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // a rule ends at ';', at the start of the next rule, at the second %% or at the end of input
+    fn alternatives(&mut self) -> Result<Vec<RuleAlternative>, LoadError> {
+        let mut alternatives: Vec<RuleAlternative> = vec![vec![]];
+
+        while let Some(lexeme) = self.peek(0).cloned() {
+            if Self::is_marker(&lexeme) {
+                break;
+            }
+
+            let item = match &lexeme.kind {
+                LexemeKind::Punct(";") => break,
+                LexemeKind::Word(_)
+                    if matches!(
+                        self.peek(1),
+                        Some(Lexeme {
+                            kind: LexemeKind::Punct(":"),
+                            ..
+                        })
+                    ) =>
+                {
+                    break
+                }
+                LexemeKind::Punct("|") => {
+                    alternatives.push(vec![]);
+                    None
+                }
+                LexemeKind::Word(word) => Some(Item::Symbol(word.clone())),
+                LexemeKind::Literal(text) => Some(match self.aliases.get(text) {
+                    Some(token) => Item::Symbol(token.clone()),
+                    None => Item::Literal(self.rules.literal(text)),
+                }),
+                LexemeKind::Directive(name) if name == "empty" => Some(Item::Epsilon),
+                LexemeKind::Directive(name)
+                    if ["prec", "dprec", "merge"].contains(&name.as_str()) =>
+                {
+                    self.position += 1;
+                    None
+                }
+                LexemeKind::Directive(_) => None,
+                _ => {
+                    return Err(
+                        lexeme.error(format!("unexpected {} inside a rule", lexeme.describe()))
+                    )
+                }
+            };
+
+            if let Some(item) = item {
+                alternatives.last_mut().unwrap().push((item, lexeme));
+            }
+            self.position += 1;
+        }
+
+        Ok(alternatives)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::load::LoadError;
+    use crate::yacc::parse;
+
+    const CALCULATOR: &str = r#"
+%{
+#include <stdio.h>
+int yylex(void);
+%}
+
+%union { int value; char *name; }
+%token <value> NUM 258
+%token LE "<="
+%left '+' '-'
+%left '*'
+%type <value> expr term
+%start input
+
+%%
+
+input: %empty
+     | input line
+     ;
+
+line: '\n' { /* skip } */ }
+    | expr ';' { printf("%d\n", $1); }
+    ;
+
+expr: expr '+' term { $$ = $1 + $3; }
+    | expr "<=" term
+    | '-' expr %prec '*'
+    | term
+    ;
+
+term: NUM | '(' expr ')' { $$ = $2; }
+
+%%
+
+int main(void) { return yyparse(); }
+"#;
+
+    #[test]
+    fn test_import_rules() {
+        let grammar = parse(CALCULATOR).unwrap().grammar;
+
+        assert_eq!(grammar.initial_symbol, "input");
+        assert_eq!(grammar.variables, vec!["input", "line", "expr", "term"]);
+        assert_eq!(
+            grammar.terminals,
+            vec!["NUM", "LE", "+", "-", "*", "\\n", ";", "(", ")"]
+        );
+
+        let derivations: Vec<(&str, &str)> = grammar
+            .productions
+            .iter()
+            .map(|p| (p.variable.as_str(), p.derivation.as_str()))
+            .collect();
+        assert_eq!(
+            derivations,
+            vec![
+                ("input", "£ | input line"),
+                ("line", "\\n | expr ;"),
+                ("expr", "expr + term | expr LE term | - expr | term"),
+                ("term", "NUM | ( expr )"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_analysis_runs_on_import() {
+        let mut grammar = parse("%token id\n%%\ns: s ',' id | id ;").unwrap().grammar;
+        grammar.compute_follows();
+
+        assert!(!grammar.ll1_conflicts().is_ll1());
+    }
+
+    #[test]
+    fn test_literals_with_bars() {
+        let yacc =
+            parse("%token ID\n%left '|' \"||\"\n%%\ne : e '|' e | e \"||\" e | ID ;").unwrap();
+
+        assert_eq!(yacc.grammar.terminals, vec!["ID", "¦", "¦¦"]);
+        assert_eq!(
+            yacc.grammar.productions[0].derivation,
+            "e ¦ e | e ¦¦ e | ID"
+        );
+        assert_eq!(yacc.source_text("¦¦"), "||");
+        assert_eq!(yacc.source_text("ID"), "ID");
+    }
+
+    #[test]
+    fn test_import_errors() {
+        assert_eq!(
+            parse("%token A\nS: A"),
+            Err(LoadError::Syntax {
+                line: 2,
+                column: 2,
+                message: "unexpected ':' in '%token'".to_string()
+            })
+        );
+        assert_eq!(
+            parse("%%\ns: a { b ;"),
+            Err(LoadError::Syntax {
+                line: 2,
+                column: 6,
+                message: "'{' is never closed".to_string()
+            })
+        );
+        assert_eq!(
+            parse("%%\ns a ;"),
+            Err(LoadError::Syntax {
+                line: 2,
+                column: 3,
+                message: "expected ':' after 's', found 'a'".to_string()
+            })
+        );
+    }
+}